@@ -0,0 +1,144 @@
+//! Bech32 external address format
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+const CHECKSUM_LENGTH: usize = 6;
+
+const MAX_ADDRESS_LENGTH: usize = 90;
+
+
+/// Encodes an account id as a bech32 address with the given human readable part
+pub fn encode_address(hrp: &str, account_id: &str) -> Result<String, &'static str> {
+    let hrp = hrp.to_lowercase();
+    check_hrp(&hrp)?;
+
+    let mut data = convert_bits(account_id.as_bytes(), 8, 5, true)?;
+    let checksum = create_checksum(&hrp, &data);
+    data.extend_from_slice(&checksum);
+
+    let mut address = hrp;
+    address.push('1');
+    for value in data {
+        address.push(CHARSET[value as usize] as char);
+    }
+
+    if address.len() > MAX_ADDRESS_LENGTH {
+        return Err("Account id is too long for an address");
+    }
+    Ok(address)
+}
+
+/// Decodes a bech32 address back into its account id, validating the
+/// checksum and that it was made for the expected human readable part
+pub fn decode_address(hrp: &str, address: &str) -> Result<String, &'static str> {
+    if address.len() > MAX_ADDRESS_LENGTH {
+        return Err("Address is too long");
+    }
+    if address.chars().any(|c| c.is_ascii_lowercase()) && address.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("Address mixes upper and lower case");
+    }
+
+    let address = address.to_lowercase();
+    let separator = match address.rfind('1') {
+        Some(position) => position,
+        None => return Err("Address has no separator"),
+    };
+
+    let (address_hrp, encoded) = (&address[..separator], &address[separator + 1..]);
+    if address_hrp != hrp.to_lowercase() {
+        return Err("Address belongs to a different chain");
+    }
+    if encoded.len() < CHECKSUM_LENGTH {
+        return Err("Address is too short");
+    }
+
+    let mut data = Vec::with_capacity(encoded.len());
+    for c in encoded.bytes() {
+        match CHARSET.iter().position(|&x| x == c) {
+            Some(value) => data.push(value as u8),
+            None => return Err("Address contains an invalid character"),
+        }
+    }
+
+    if !verify_checksum(address_hrp, &data) {
+        return Err("Address checksum is invalid");
+    }
+
+    data.truncate(data.len() - CHECKSUM_LENGTH);
+    let bytes = convert_bits(&data, 5, 8, false)?;
+    String::from_utf8(bytes).map_err(|_| "Address does not hold a valid account id")
+}
+
+
+fn check_hrp(hrp: &str) -> Result<(), &'static str> {
+    if hrp.is_empty() || hrp.bytes().any(|c| !(33..=126).contains(&c)) {
+        return Err("Invalid human readable part");
+    }
+    Ok(())
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum: u32 = 1;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+fn create_checksum(hrp: &str, data: &[u8]) -> Vec<u8> {
+    let mut values = expand_hrp(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_LENGTH]);
+
+    let checksum = polymod(&values) ^ 1;
+    (0..CHECKSUM_LENGTH)
+        .map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8)
+        .collect()
+}
+
+fn verify_checksum(hrp: &str, data: &[u8]) -> bool {
+    let mut values = expand_hrp(hrp);
+    values.extend_from_slice(data);
+    polymod(&values) == 1
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, &'static str> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut converted = Vec::new();
+
+    for &value in data {
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            converted.push(((acc << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max_value) != 0 {
+        return Err("Address has invalid padding");
+    }
+
+    Ok(converted)
+}
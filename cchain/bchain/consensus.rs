@@ -0,0 +1,287 @@
+//! Pluggable consensus rules shared by the block import pipeline
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::{Amount, Block, Blockchain, WorldStateReader};
+
+/// Bits in a block hash, the most zero bits a difficulty can ask for
+pub const HASH_BITS: u32 = 512;
+
+
+/// Consensus rules a Blockchain applies to every block it appends
+pub trait ConsensusEngine: Debug {
+    /// Checks the engine's rules for a block about to be appended
+    fn validate_header(&self, chain: &Blockchain, block: &Block) -> Result<(), String>;
+
+    /// Runs after the block's transactions were applied, before it is sealed.
+    /// Engines with protocol bookkeeping (rewards, validator sets) do it here.
+    fn finalize_block(&self, _chain: &mut Blockchain, _block: &Block) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Prepares a new block so it passes validate_header
+    fn author_block(&self, chain: &Blockchain, block: &mut Block) -> Result<(), String>;
+}
+
+/// Development engine: any correctly hashed block is accepted at once
+#[derive(Clone,Debug,Default)]
+pub struct InstantSeal;
+
+/// Blocks must have a hash beginning with `difficulty` zero bits
+#[derive(Clone,Debug)]
+pub struct ProofOfWork{
+    difficulty: u32,
+}
+
+/// Validators take turns authoring blocks, in the order they are listed,
+/// and each must hold at least `min_stake` tokens
+#[derive(Clone,Debug)]
+pub struct ProofOfStake{
+    validators: Vec<String>,
+    min_stake: Amount,
+}
+
+/// Consensus a chain runs, as set in its ChainSpec
+#[derive(Clone,Debug,Default)]
+pub enum ConsensusSpec{
+    #[default]
+    InstantSeal,
+    ProofOfWork{difficulty: u32},
+    ProofOfStake{validators: Vec<String>, min_stake: Amount},
+}
+
+
+impl ProofOfWork {
+    /// Fails for a difficulty over HASH_BITS, which no hash could meet
+    pub fn new(difficulty: u32) -> Result<Self, String> {
+        if difficulty > HASH_BITS {
+            return Err(format!("A difficulty of {} bits is more than the {} bits of a hash", difficulty, HASH_BITS));
+        }
+        Ok(ProofOfWork{difficulty})
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+}
+
+impl ProofOfStake {
+    /// Fails for an empty validator set or one listing a validator twice
+    pub fn new(validators: Vec<String>, min_stake: Amount) -> Result<Self, String> {
+        if validators.is_empty() {
+            return Err("The validator set is empty".into());
+        }
+        for (i, validator) in validators.iter().enumerate() {
+            if validators[..i].contains(validator) {
+                return Err(format!("{} is listed twice in the validator set", validator));
+            }
+        }
+        Ok(ProofOfStake{validators, min_stake})
+    }
+
+    pub fn validators(&self) -> &[String] {
+        &self.validators
+    }
+
+    pub fn min_stake(&self) -> Amount {
+        self.min_stake
+    }
+
+    /// Validator whose turn it is to author the block at `height`
+    pub fn proposer(&self, height: u64) -> &str {
+        &self.validators[(height % self.validators.len() as u64) as usize]
+    }
+
+    fn check_stake(&self, chain: &Blockchain, validator: &str) -> Result<(), String> {
+        match chain.get_balance(validator) {
+            Some(stake) if stake >= self.min_stake => Ok(()),
+            _ => Err(format!("Validator {} does not hold the minimum stake", validator)),
+        }
+    }
+}
+
+impl ConsensusSpec {
+    /// Builds the engine, failing if its settings are invalid
+    pub fn build(&self) -> Result<Arc<dyn ConsensusEngine>, String> {
+        Ok(match self {
+            ConsensusSpec::InstantSeal => Arc::new(InstantSeal),
+            ConsensusSpec::ProofOfWork{difficulty} => Arc::new(ProofOfWork::new(*difficulty)?),
+            ConsensusSpec::ProofOfStake{validators, min_stake} => Arc::new(ProofOfStake::new(validators.clone(), *min_stake)?),
+        })
+    }
+}
+
+
+impl ConsensusEngine for InstantSeal {
+    fn validate_header(&self, _chain: &Blockchain, _block: &Block) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn author_block(&self, _chain: &Blockchain, block: &mut Block) -> Result<(), String> {
+        block.update_hash();
+        Ok(())
+    }
+}
+
+impl ConsensusEngine for ProofOfWork {
+    fn validate_header(&self, _chain: &Blockchain, block: &Block) -> Result<(), String> {
+        if leading_zero_bits(&block.calculate_hash()) < self.difficulty {
+            return Err("The block hash does not meet the difficulty".into());
+        }
+        Ok(())
+    }
+
+    /// Searches nonces from the block's current one up to u128::MAX, and
+    /// fails rather than wrapping around if none of them is small enough
+    fn author_block(&self, _chain: &Blockchain, block: &mut Block) -> Result<(), String> {
+        while leading_zero_bits(&block.calculate_hash()) < self.difficulty {
+            block.nonce = match block.nonce.checked_add(1) {
+                Some(nonce) => nonce,
+                None => return Err("No nonce left meets the difficulty".into()),
+            };
+        }
+        block.update_hash();
+        Ok(())
+    }
+}
+
+impl ConsensusEngine for ProofOfStake {
+    /// The block must be authored by the validator whose turn it is, holding
+    /// the minimum stake. Genesis only needs the right author, it creates
+    /// the stakes.
+    fn validate_header(&self, chain: &Blockchain, block: &Block) -> Result<(), String> {
+        let proposer = self.proposer(chain.height());
+        match block.author() {
+            Some(author) if author == proposer => {}
+            Some(author) if self.validators.iter().any(|v| v == author) => {
+                return Err(format!("It is {}'s turn to author the block, not {}'s", proposer, author));
+            }
+            Some(author) => return Err(format!("{} is not a validator", author)),
+            None => return Err("The block has no author".into()),
+        }
+        if chain.height() == 0 {
+            return Ok(());
+        }
+        self.check_stake(chain, proposer)
+    }
+
+    /// Rejects a block that leaves its author below the minimum stake
+    fn finalize_block(&self, chain: &mut Blockchain, block: &Block) -> Result<(), String> {
+        match block.author() {
+            Some(author) => self.check_stake(chain, author),
+            None => Err("The block has no author".into()),
+        }
+    }
+
+    fn author_block(&self, chain: &Blockchain, block: &mut Block) -> Result<(), String> {
+        block.author = Some(self.proposer(chain.height()).to_string());
+        block.update_hash();
+        Ok(())
+    }
+}
+
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChainSpec, Transaction, TransactionData};
+
+    fn staked_chain() -> Blockchain {
+        let spec = ChainSpec{
+            consensus: ConsensusSpec::ProofOfStake{validators: vec!["alice".into(), "bob".into()], min_stake: Amount::from(100u64)},
+            ..ChainSpec::default()
+        };
+        let mut chain = Blockchain::from_spec(&spec).unwrap();
+
+        let mut genesis = Block::new(None);
+        genesis.set_timestamp(1_000);
+        for id in ["alice", "bob"].iter() {
+            genesis.add_transaction(Transaction::new("genesis".into(), TransactionData::CreateUserAccount(id.to_string()), 0));
+            genesis.add_transaction(Transaction::new("genesis".into(), TransactionData::CreateTokens{receiver: id.to_string(), amount: Amount::from(150u64)}, 0));
+        }
+        chain.author_block(&mut genesis).unwrap();
+        chain.append_block(genesis).unwrap();
+        chain
+    }
+
+    fn next_block(chain: &Blockchain) -> Block {
+        let mut block = Block::new(chain.get_last_block_hash());
+        block.set_timestamp(chain.median_time_past() + 1);
+        block
+    }
+
+    #[test]
+    fn proof_of_work_rejects_a_difficulty_no_hash_can_meet() {
+        assert!(ProofOfWork::new(HASH_BITS).is_ok());
+        assert!(ProofOfWork::new(HASH_BITS + 1).is_err());
+        assert!(ConsensusSpec::ProofOfWork{difficulty: u32::MAX}.build().is_err());
+    }
+
+    #[test]
+    fn proof_of_work_stops_when_the_nonces_run_out() {
+        let chain = Blockchain::new();
+        let mut block = Block::new(None);
+        block.set_nonce(u128::MAX);
+        assert!(ProofOfWork::new(HASH_BITS).unwrap().author_block(&chain, &mut block).is_err());
+        assert_eq!(block.nonce(), u128::MAX);
+
+        let mut block = Block::new(None);
+        ProofOfWork::new(4).unwrap().author_block(&chain, &mut block).unwrap();
+        assert!(leading_zero_bits(&block.calculate_hash()) >= 4);
+    }
+
+    #[test]
+    fn proof_of_stake_checks_its_validator_set() {
+        assert!(ProofOfStake::new(Vec::new(), Amount::from(1u64)).is_err());
+        assert!(ProofOfStake::new(vec!["alice".into(), "alice".into()], Amount::from(1u64)).is_err());
+    }
+
+    #[test]
+    fn validators_take_turns() {
+        let mut chain = staked_chain();
+        assert_eq!(chain.blocks()[0].author(), Some("alice"));
+
+        let mut block = next_block(&chain);
+        block.set_author(Some("alice".into()));
+        assert!(chain.append_block(block.clone()).unwrap_err().contains("bob's turn"));
+        block.set_author(Some("carol".into()));
+        assert!(chain.append_block(block.clone()).unwrap_err().contains("not a validator"));
+        block.set_author(None);
+        assert!(chain.append_block(block.clone()).is_err());
+
+        chain.author_block(&mut block).unwrap();
+        assert_eq!(block.author(), Some("bob"));
+        chain.append_block(block).unwrap();
+    }
+
+    #[test]
+    fn a_block_cannot_leave_its_author_under_the_minimum_stake() {
+        let mut chain = staked_chain();
+        let mut block = next_block(&chain);
+        block.add_transaction(Transaction::new("bob".into(), TransactionData::TransferTokens{to: "alice".into(), amount: Amount::from(60u64)}, 0));
+        chain.author_block(&mut block).unwrap();
+        assert!(chain.append_block(block).unwrap_err().contains("minimum stake"));
+        assert_eq!(chain.get_balance("bob"), Some(Amount::from(150u64)));
+
+        let mut block = next_block(&chain);
+        block.add_transaction(Transaction::new("bob".into(), TransactionData::TransferTokens{to: "alice".into(), amount: Amount::from(50u64)}, 0));
+        chain.author_block(&mut block).unwrap();
+        chain.append_block(block).unwrap();
+        assert_eq!(chain.get_balance("bob"), Some(Amount::from(100u64)));
+    }
+}
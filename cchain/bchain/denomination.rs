@@ -0,0 +1,126 @@
+//! Conversion between integer base units and human readable token amounts
+
+use crate::Amount;
+
+/// Most decimals a token can have, the most for which one whole token
+/// still fits in an `Amount`
+#[cfg(not(feature = "u256"))]
+pub const MAX_DECIMALS: u32 = 38;
+#[cfg(feature = "u256")]
+pub const MAX_DECIMALS: u32 = 77;
+
+
+/// Parses a token amount such as `1.5` or `1,000.25` into base units,
+/// where one token is `10^decimals` base units
+pub fn from_display(display: &str, decimals: u32) -> Result<Amount, &'static str> {
+    if decimals > MAX_DECIMALS {
+        return Err("Token has more decimals than an amount can hold");
+    }
+    let display = display.trim();
+    let (whole, fraction) = match display.find('.') {
+        Some(position) => (&display[..position], &display[position + 1..]),
+        None => (display, ""),
+    };
+
+    if whole.is_empty() && fraction.is_empty() {
+        return Err("Empty amount");
+    }
+    if fraction.len() > decimals as usize {
+        return Err("Amount has more decimals than the token");
+    }
+    if !valid_grouping(whole) {
+        return Err("Misplaced thousands separator");
+    }
+
+    let mut value = Amount::default();
+    let padding = std::iter::repeat_n('0', decimals as usize - fraction.len());
+    let digits = whole.chars().filter(|&c| c != ',')
+        .chain(fraction.chars())
+        .chain(padding);
+
+    for c in digits {
+        let digit = match c.to_digit(10) {
+            Some(digit) => digit,
+            None => return Err("Invalid digit in amount"),
+        };
+        value = value.checked_mul(Amount::from(10u64))
+            .and_then(|v| v.checked_add(Amount::from(digit as u64)))
+            .ok_or("Amount is too large")?;
+    }
+    Ok(value)
+}
+
+/// Formats base units as tokens with thousands separators, e.g. `1,234.5`.
+/// Trailing zeros of the fraction are dropped.
+pub fn to_display(amount: Amount, decimals: u32) -> Result<String, &'static str> {
+    if decimals > MAX_DECIMALS {
+        return Err("Token has more decimals than an amount can hold");
+    }
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+
+    let padded = if digits.len() <= decimals {
+        format!("{}{}", "0".repeat(decimals + 1 - digits.len()), digits)
+    } else {
+        digits
+    };
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+
+    let mut display = group_thousands(whole);
+    let fraction = fraction.trim_end_matches('0');
+    if !fraction.is_empty() {
+        display.push('.');
+        display.push_str(fraction);
+    }
+    Ok(display)
+}
+
+
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Separators, if used, must split the whole part into groups of three
+fn valid_grouping(whole: &str) -> bool {
+    if !whole.contains(',') {
+        return true;
+    }
+    let groups: Vec<&str> = whole.split(',').collect();
+    !groups[0].is_empty() && groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_token_of_the_most_decimals_fits_in_an_amount() {
+        let one = from_display("1", MAX_DECIMALS).unwrap();
+        assert_eq!(to_display(one, MAX_DECIMALS), Ok("1".to_string()));
+        assert_eq!(from_display("1", MAX_DECIMALS + 1), Err("Token has more decimals than an amount can hold"));
+        // ten tokens overflow, so one more decimal could not hold even one
+        assert_eq!(from_display("10", MAX_DECIMALS), Err("Amount is too large"));
+    }
+
+    #[test]
+    fn too_many_decimals_are_rejected_before_any_work() {
+        assert_eq!(from_display("1.5", u32::MAX), Err("Token has more decimals than an amount can hold"));
+        assert_eq!(to_display(Amount::from(1u64), u32::MAX), Err("Token has more decimals than an amount can hold"));
+    }
+
+    #[test]
+    fn amounts_round_trip_through_their_display() {
+        for &(display, decimals) in [("0", 0), ("1,234.5", 2), ("0.001", 3), ("1,000,000", 6)].iter() {
+            let amount = from_display(display, decimals).unwrap();
+            assert_eq!(to_display(amount, decimals).unwrap(), display);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Local development chain: instant blocks, funded accounts and time controls
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::{Amount, Block, Blockchain, Transaction, TransactionData};
+
+/// Tokens minted to each dev account
+pub const DEV_ACCOUNT_TOKENS: u128 = 1_000_000_000;
+
+
+/// Chain that seals a block as soon as a transaction is submitted, with a
+/// controllable clock so time dependent logic can be tried out quickly
+#[derive(Debug)]
+pub struct DevChain{
+    chain: Blockchain,
+
+    clock: u64,
+}
+
+
+/// Id of the `index`th pre-funded dev account
+pub fn dev_account(index: usize) -> String {
+    format!("dev{}", index)
+}
+
+
+impl DevChain {
+    /// Starts a chain whose genesis block funds `accounts` dev accounts
+    pub fn new(accounts: usize) -> Self {
+        let mut dev = DevChain{
+            chain: Blockchain::new(),
+            clock: crate::unix_time_now(),
+        };
+        dev.chain.set_max_future_drift(u64::MAX);
+
+        let mut genesis = Block::new(None);
+        for i in 0..accounts {
+            let id = dev_account(i);
+            genesis.add_transaction(Transaction::new("dev".into(), TransactionData::CreateUserAccount(id.clone()), 0));
+            genesis.add_transaction(Transaction::new("dev".into(), TransactionData::CreateTokens{receiver: id, amount: Amount::from(DEV_ACCOUNT_TOKENS)}, 0));
+        }
+        dev.seal(genesis).expect("Dev genesis block is valid");
+        dev
+    }
+
+    pub fn chain(&self) -> &Blockchain {
+        &self.chain
+    }
+
+    /// Current dev clock, in seconds since the unix epoch
+    pub fn now(&self) -> u64 {
+        self.clock
+    }
+
+    /// Adds the transaction to the pending pool and seals it into a block right
+    /// away. A transaction that fails is dropped from the pool.
+    pub fn submit(&mut self, mut transaction: Transaction) -> Result<(), String> {
+        transaction.created_at = UNIX_EPOCH + Duration::from_secs(self.clock);
+        let hash = transaction.calculate_hash();
+        self.chain.add_pending_transaction(transaction.clone());
+
+        let mut block = Block::new(self.chain.get_last_block_hash());
+        block.add_transaction(transaction);
+        let result = self.seal(block);
+
+        if result.is_err() {
+            self.chain.evict_pending_transaction(&hash);
+        }
+        result
+    }
+
+    /// Moves the dev clock forward without sealing blocks
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.clock = self.clock.saturating_add(seconds);
+    }
+
+    /// Seals `blocks` empty blocks, e.g. to reach a subscription payment
+    pub fn advance_blocks(&mut self, blocks: usize) -> Result<(), String> {
+        for _ in 0..blocks {
+            let block = Block::new(self.chain.get_last_block_hash());
+            self.seal(block)?;
+        }
+        Ok(())
+    }
+
+    /// Stamps the block with the dev clock, one second past the previous block
+    fn seal(&mut self, mut block: Block) -> Result<(), String> {
+        self.clock = self.clock.max(self.chain.median_time_past() + 1);
+        block.set_timestamp(self.clock);
+        self.chain.author_block(&mut block)?;
+        self.chain.append_block(block)?;
+        self.clock += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Comparison of two chains, to find where two nodes split
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::Blockchain;
+
+
+/// Differences between a `left` and a `right` chain
+#[derive(Clone,Debug,PartialEq)]
+pub struct ChainDiff{
+    /// First height whose blocks differ, None when one chain extends the other
+    pub first_divergent_height: Option<usize>,
+
+    /// Hashes of transactions only the right chain includes
+    pub missing_in_left: Vec<Vec<u8>>,
+
+    /// Hashes of transactions only the left chain includes
+    pub missing_in_right: Vec<Vec<u8>>,
+}
+
+
+pub fn compare(left: &Blockchain, right: &Blockchain) -> ChainDiff {
+    let first_divergent_height = left.blocks().iter()
+        .zip(right.blocks().iter())
+        .position(|(l, r)| l.hash() != r.hash());
+
+    let left_transactions = transaction_hashes(left);
+    let right_transactions = transaction_hashes(right);
+
+    ChainDiff{
+        first_divergent_height,
+        missing_in_left: missing(&right_transactions, &left_transactions),
+        missing_in_right: missing(&left_transactions, &right_transactions),
+    }
+}
+
+/// Ids of the accounts whose state differs between the chains after their
+/// first `height` blocks
+pub fn state_diff_at(left: &Blockchain, right: &Blockchain, height: usize) -> Result<Vec<String>, String> {
+    let left = left.state_at(height)?;
+    let right = right.state_at(height)?;
+
+    let ids: BTreeSet<&String> = left.accounts.keys().chain(right.accounts.keys()).collect();
+    Ok(ids.into_iter()
+        .filter(|id| left.accounts.get(*id) != right.accounts.get(*id))
+        .cloned()
+        .collect())
+}
+
+
+fn transaction_hashes(chain: &Blockchain) -> Vec<Vec<u8>> {
+    chain.blocks().iter()
+        .flat_map(|block| block.transactions().iter().map(|t| t.calculate_hash()))
+        .collect()
+}
+
+/// Hashes in `from`, in chain order, that `other` doesn't have
+fn missing(from: &[Vec<u8>], other: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let other: HashSet<&Vec<u8>> = other.iter().collect();
+    from.iter().filter(|hash| !other.contains(hash)).cloned().collect()
+}
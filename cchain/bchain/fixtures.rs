@@ -0,0 +1,146 @@
+//! Reproducible chains for tests and benchmarks
+
+use std::collections::HashMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::{Amount, Block, Blockchain, Transaction, TransactionData};
+
+/// Timestamp of every fixture genesis block
+pub const FIXTURE_GENESIS_TIME: u64 = 1_600_000_000;
+
+/// Seconds between fixture blocks
+pub const FIXTURE_BLOCK_TIME: u64 = 10;
+
+/// Tokens minted to each genesis account
+pub const FIXTURE_INITIAL_TOKENS: u128 = 1_000_000;
+
+
+/// Shape of a generated chain. The same config always yields the same chain.
+#[derive(Clone,Debug)]
+pub struct FixtureConfig{
+    pub seed: u64,
+
+    /// Blocks after the genesis block
+    pub blocks: usize,
+
+    /// Accounts funded in the genesis block
+    pub accounts: usize,
+
+    pub transactions_per_block: usize,
+
+    /// Share, out of 100, of transactions creating accounts; the rest are transfers
+    pub new_account_percent: u8,
+}
+
+#[derive(Debug)]
+pub struct Fixture{
+    /// Config, seed included, the chain was generated from
+    pub config: FixtureConfig,
+
+    pub chain: Blockchain,
+}
+
+struct SplitMix64(u64);
+
+
+impl Default for FixtureConfig {
+    fn default() -> Self {
+        FixtureConfig{
+            seed: 0,
+            blocks: 10,
+            accounts: 10,
+            transactions_per_block: 5,
+            new_account_percent: 10,
+        }
+    }
+}
+
+
+/// Builds a chain from the config, panicking with the seed if it can't be appended
+pub fn generate(config: &FixtureConfig) -> Fixture {
+    let mut rng = SplitMix64(config.seed);
+    let mut chain = Blockchain::new();
+    let mut balances: HashMap<String, u128> = HashMap::new();
+    let mut ids: Vec<String> = Vec::new();
+    let mut nonces: HashMap<String, u128> = HashMap::new();
+
+    let mut timestamp = FIXTURE_GENESIS_TIME;
+    let mut genesis = Block::new(None);
+    for i in 0..config.accounts {
+        let id = format!("account{}", i);
+        let receiver = id.clone();
+        genesis.add_transaction(transaction("genesis", TransactionData::CreateUserAccount(id.clone()), &mut nonces, timestamp));
+        genesis.add_transaction(transaction("genesis", TransactionData::CreateTokens{receiver, amount: Amount::from(FIXTURE_INITIAL_TOKENS)}, &mut nonces, timestamp));
+        balances.insert(id.clone(), FIXTURE_INITIAL_TOKENS);
+        ids.push(id);
+    }
+    append(&mut chain, genesis, timestamp, config.seed);
+
+    for _ in 0..config.blocks {
+        timestamp += FIXTURE_BLOCK_TIME;
+        let mut block = Block::new(chain.get_last_block_hash());
+
+        for _ in 0..config.transactions_per_block {
+            if ids.is_empty() {
+                break;
+            }
+            let from = ids[rng.below(ids.len() as u64) as usize].clone();
+
+            let record = if rng.below(100) < config.new_account_percent as u64 {
+                let id = format!("account{}", ids.len());
+                balances.insert(id.clone(), 0);
+                ids.push(id.clone());
+                TransactionData::CreateUserAccount(id)
+            } else {
+                let to = ids[rng.below(ids.len() as u64) as usize].clone();
+                let balance = balances[&from];
+                let amount = if balance == 0 { 0 } else { rng.below(balance as u64 / 2 + 1) as u128 };
+
+                if from != to {
+                    *balances.get_mut(&from).unwrap() -= amount;
+                    *balances.get_mut(&to).unwrap() += amount;
+                }
+                TransactionData::TransferTokens{to, amount: Amount::from(amount)}
+            };
+
+            block.add_transaction(transaction(&from, record, &mut nonces, timestamp));
+        }
+
+        append(&mut chain, block, timestamp, config.seed);
+    }
+
+    Fixture{
+        config: config.clone(),
+        chain,
+    }
+}
+
+fn transaction(from: &str, record: TransactionData, nonces: &mut HashMap<String, u128>, timestamp: u64) -> Transaction {
+    let nonce = nonces.entry(from.to_string()).or_insert(0);
+    let mut transaction = Transaction::new(from.to_string(), record, *nonce);
+    transaction.created_at = UNIX_EPOCH + Duration::from_secs(timestamp);
+    *nonce += 1;
+    transaction
+}
+
+fn append(chain: &mut Blockchain, mut block: Block, timestamp: u64, seed: u64) {
+    block.set_timestamp(timestamp);
+    if let Err(err) = chain.append_block(block) {
+        panic!("Fixture with seed {} produced an invalid block: {}", seed, err);
+    }
+}
+
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
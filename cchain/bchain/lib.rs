@@ -1,26 +1,150 @@
 ///Blockchain logic
 
-use std::collections::HashMap;
-use std::time::SystemTime;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::{Bound, Deref};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use blake2::{Blake2b, Digest};
 
+use consensus::{ConsensusEngine, ConsensusSpec, InstantSeal};
+
+pub mod address;
+pub mod consensus;
+pub mod denomination;
+pub mod dev;
+pub mod diff;
+pub mod fixtures;
+#[cfg(feature = "memory")]
+pub mod memory;
+pub mod u256;
+
+/// Type of token balances and amounts, 256 bits wide with the `u256` feature
+#[cfg(not(feature = "u256"))]
+pub type Amount = u128;
+#[cfg(feature = "u256")]
+pub type Amount = u256::U256;
+
 
 #[derive(Debug,Clone)]
 pub struct Blockchain{
-    pub blocks: Vec<Block>,
+    blocks: Vec<SealedBlock>,
+
+    pub(crate) accounts: HashMap<String, Account>,
+
+    pending_transactions: Vec<Transaction>,
+
+    dropped_transactions: HashMap<Vec<u8>, String>,
+
+    subscriptions: Vec<Subscription>,
+
+    /// Reported values per feed, round and reporter
+    oracle_reports: BTreeMap<String, BTreeMap<u64, BTreeMap<String, u128>>>,
 
-    pub accounts: HashMap<String, Account>,
+    account_id_rules: AccountIdRules,
 
-    pending_transactions: Vec<Transaction>
+    max_future_drift: u64,
+
+    dust_rules: DustRules,
+
+    consensus: Arc<dyn ConsensusEngine>,
     
 }
 
-pub trait WorldState {
+/// Settings a chain starts from, so nodes that share it build the same chain
+#[derive(Clone,Debug)]
+pub struct ChainSpec{
+    pub consensus: ConsensusSpec,
+
+    pub account_id_rules: AccountIdRules,
+
+    pub dust_rules: DustRules,
+
+    /// Allowance, in seconds, for block timestamps ahead of the local clock
+    pub max_future_drift: u64,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        ChainSpec {
+            consensus: ConsensusSpec::default(),
+            account_id_rules: AccountIdRules::default(),
+            dust_rules: DustRules::default(),
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
+        }
+    }
+}
+
+/// Number of previous blocks whose median timestamp a new block must exceed
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Default allowance, in seconds, for block timestamps ahead of the local clock
+pub const DEFAULT_MAX_FUTURE_DRIFT: u64 = 2 * 60 * 60;
+
+/// Read-only view of the chain state, enough for validation and queries
+pub trait WorldStateReader {
     fn get_user_ids(&self) -> Vec<String>;
-    fn get_account_by_id_mut(&mut self, id: &String) -> Option<&mut Account>; 
-    fn get_account_by_id(&self, id: &String) -> Option<& Account>;
+    fn get_account_by_id(&self, id: &str) -> Option<& Account>;
+    fn get_subscription(&self, from: &str, to: &str) -> Option<&Subscription>;
+    fn get_oracle_report(&self, feed_id: &str, round: u64, reporter: &str) -> Option<u128>;
+
+    /// Height of the block being appended, i.e. the blocks already in the chain
+    fn height(&self) -> u64;
+
+    /// Anti-spam limits applied to transfers, none by default
+    fn dust_rules(&self) -> DustRules {
+        DustRules::default()
+    }
+
+    /// Rules the ids of new accounts must follow
+    fn account_id_rules(&self) -> AccountIdRules {
+        AccountIdRules::default()
+    }
+
+    fn account_exists(&self, id: &str) -> bool {
+        self.get_account_by_id(id).is_some()
+    }
+
+    fn get_balance(&self, id: &str) -> Option<Amount> {
+        self.get_account_by_id(id).map(|account| account.tokens)
+    }
+
+    fn get_store_value(&self, id: &str, key: &str) -> Option<&String> {
+        self.get_account_by_id(id).and_then(|account| account.store.get(key))
+    }
+
+    /// Store entries of an account whose key starts with `prefix`, in key order
+    fn store_scan<'a>(&'a self, id: &str, prefix: &'a str) -> Vec<(&'a str, &'a str)> {
+        match self.get_account_by_id(id) {
+            Some(account) => account.store_scan(prefix).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Owners always have full access to their own store
+    fn has_store_access(&self, owner: &str, grantee: &str, key: &str, access: StoreAccess) -> bool {
+        if owner == grantee {
+            return true;
+        }
+        match self.get_account_by_id(owner) {
+            Some(account) => account.store_grants.iter().any(|grant| {
+                grant.grantee == grantee
+                    && key.starts_with(grant.prefix.as_str())
+                    && (grant.access == StoreAccess::Write || access == StoreAccess::Read)
+            }),
+            None => false,
+        }
+    }
+}
+
+/// Mutable access to the chain state, used only when applying transactions
+pub trait WorldStateWriter: WorldStateReader {
+    fn get_account_by_id_mut(&mut self, id: &str) -> Option<&mut Account>; 
     fn create_account(&mut self, id: String, account_type: AccountType) -> Result<(),&'static str>;
-    
+    fn create_subscription(&mut self, from: String, to: String, amount: Amount, interval_blocks: u64) -> Result<(), &'static str>;
+    fn get_subscription_mut(&mut self, from: &str, to: &str) -> Option<&mut Subscription>;
+    fn cancel_subscription(&mut self, from: &str, to: &str) -> Result<(), &'static str>;
+    fn publish_oracle_value(&mut self, feed_id: String, round: u64, reporter: String, value: u128) -> Result<(), &'static str>;
 }
 
 #[derive(Debug,Clone)]
@@ -29,6 +153,28 @@ pub struct Block {
     prev_hash: Option<String>, 
     hash: Option<String>, 
     nonce: u128, 
+    timestamp: u64,
+    /// Validator that authored the block, for engines that have them
+    pub(crate) author: Option<String>,
+}
+
+/// Block that has been appended to a chain. It only hands out shared
+/// references, so its transactions and hash can no longer change.
+#[derive(Debug,Clone)]
+pub struct SealedBlock{
+    block: Block,
+
+    execution_time: ExecutionTime,
+}
+
+/// Wall-clock time spent executing a block, measured by this node
+#[derive(Debug,Clone,Default)]
+pub struct ExecutionTime{
+    /// Time for each transaction, in block order
+    pub transactions: Vec<Duration>,
+
+    /// Time for the whole block, subscriptions and finalization included
+    pub total: Duration,
 }
 
 #[derive(Clone,Debug)]
@@ -43,33 +189,153 @@ pub struct Transaction{
 
     signature: Option<String>, 
 
+    memo: Option<Vec<u8>>,
+
+}
+
+/// Largest memo, in bytes, a transaction may carry
+pub const MAX_MEMO_SIZE: usize = 256;
+
+
+/// Where a transaction is in its lifecycle, looked up by its hash
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionStatus{
+    Unknown,
+    Pending,
+    Included{height: usize},
+    Dropped{reason: String},
+}
+
+/// Why a transaction could not be executed
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionError{
+    /// The transaction is not valid against the state it was applied to
+    Rejected(&'static str),
+    /// A balance update that validation should have ruled out
+    Balance(BalanceError),
 }
 
+/// Balance arithmetic that failed while applying a validated transaction
+#[derive(Clone, Debug, PartialEq)]
+pub enum BalanceError{
+    Overflow{account: String, balance: Amount, amount: Amount},
+    Underflow{account: String, balance: Amount, amount: Amount},
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TransactionData{
     CreateUserAccount(String), 
     ChangeStoreValue {key: String, value: String},
-    TransferTokens{to:String, amount:u128},
-    CreateTokens{receiver: String , amount:u128},
+    ChangeSharedStoreValue {owner: String, key: String, value: String},
+    GrantStoreAccess {grantee: String, prefix: String, access: StoreAccess},
+    RevokeStoreAccess {grantee: String, prefix: String},
+    TransferTokens{to:String, amount:Amount},
+    CreateTokens{receiver: String , amount:Amount},
+    CreateSubscription{to: String, amount: Amount, interval_blocks: u64},
+    CancelSubscription{to: String},
+    ResumeSubscription{to: String},
+    CreateOracleAccount(String),
+    PublishOracleValue{feed_id: String, value: u128, round: u64},
 }
 
+/// Access an account can grant to others on its store keys
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoreAccess{
+    Read,
+    /// Write access includes read access
+    Write,
+}
 
+/// Permission on every store key starting with `prefix`
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoreGrant{
+    pub grantee: String,
+    pub prefix: String,
+    pub access: StoreAccess,
+}
 
+/// Recurring transfer paid automatically every `interval_blocks` blocks
 #[derive(Clone,Debug)]
+pub struct Subscription{
+    from: String,
+
+    to: String,
+
+    amount: Amount,
+
+    interval_blocks: u64,
+
+    next_payment: u64,
+
+    paused: bool,
+}
+
+
+
+#[derive(Clone,Debug,PartialEq)]
 pub struct Account{
     
-    store: HashMap<String,String>, 
+    store: BTreeMap<String,String>, 
+
+    store_grants: Vec<StoreGrant>,
 
     acc_type: AccountType, 
 
-    tokens: u128,
+    tokens: Amount,
 }
 
+/// Read-only copy of an account handed out by query APIs
 #[derive(Clone,Debug)]
+pub struct AccountView{
+
+    store: BTreeMap<String,String>, 
+
+    acc_type: AccountType, 
+
+    tokens: Amount,
+}
+
+/// Minimum amounts that keep the state from filling up with dust accounts
+#[derive(Clone,Debug,Default)]
+pub struct DustRules{
+    pub min_transfer: Amount,
+
+    /// Non-empty balances below this are dust
+    pub min_balance: Amount,
+
+    /// Send the whole balance instead of failing when a transfer
+    /// would leave dust behind
+    pub sweep_dust: bool,
+}
+
+/// Rules an account id has to follow before an account can be created
+#[derive(Clone,Debug)]
+pub struct AccountIdRules{
+    pub min_length: usize,
+
+    pub max_length: usize,
+
+    /// Characters allowed besides ASCII letters and digits
+    pub extra_chars: String,
+
+    /// Prefixes kept for protocol accounts, e.g. `system:`
+    pub reserved_prefixes: Vec<String>,
+}
+
+/// Protocol owned accounts, created with the genesis block
+pub const FEE_POOL_ACCOUNT: &str = "system:fee_pool";
+pub const BURN_ACCOUNT: &str = "system:burn";
+pub const TREASURY_ACCOUNT: &str = "system:treasury";
+pub const STAKING_POOL_ACCOUNT: &str = "system:staking_pool";
+
+pub const SYSTEM_ACCOUNTS: [&str; 4] = [FEE_POOL_ACCOUNT, BURN_ACCOUNT, TREASURY_ACCOUNT, STAKING_POOL_ACCOUNT];
+
+#[derive(Clone,Debug,PartialEq)]
 pub enum AccountType{
     User,
     Contract, 
+    System,
+    Oracle,
     Validator{
         correctly_validated_blocks: u128,
         incorrectly_validated_blocks: u128,
@@ -85,11 +351,59 @@ impl Blockchain {
             blocks: Vec::new(),
             accounts: HashMap::new(),
             pending_transactions: Vec::new(),
+            dropped_transactions: HashMap::new(),
+            subscriptions: Vec::new(),
+            oracle_reports: BTreeMap::new(),
+            account_id_rules: AccountIdRules::default(),
+            max_future_drift: DEFAULT_MAX_FUTURE_DRIFT,
+            dust_rules: DustRules::default(),
+            consensus: Arc::new(InstantSeal),
+        }
+    }
+
+    pub fn with_account_id_rules(account_id_rules: AccountIdRules) -> Self {
+        Blockchain {
+            account_id_rules,
+            ..Blockchain::new()
         }
     }
 
 
+    pub fn with_consensus(consensus: Arc<dyn ConsensusEngine>) -> Self {
+        Blockchain {
+            consensus,
+            ..Blockchain::new()
+        }
+    }
+
+    /// Starts an empty chain with the engine and rules `spec` selects
+    pub fn from_spec(spec: &ChainSpec) -> Result<Self, String> {
+        Ok(Blockchain {
+            account_id_rules: spec.account_id_rules.clone(),
+            max_future_drift: spec.max_future_drift,
+            dust_rules: spec.dust_rules.clone(),
+            consensus: spec.consensus.build()?,
+            ..Blockchain::new()
+        })
+    }
+
+    /// Lets the consensus engine seal (e.g. mine) a block for this chain
+    pub fn author_block(&self, block: &mut Block) -> Result<(), String> {
+        self.consensus.author_block(self, block)
+    }
+
+
     pub fn append_block(&mut self, block:Block) -> Result<(), String> {
+        self.import_block(block, None)
+    }
+
+    /// Appends a block built by this node, rejecting it when its execution
+    /// takes longer than `budget` so peers can verify it in time
+    pub fn append_local_block(&mut self, block: Block, budget: Duration) -> Result<(), String> {
+        self.import_block(block, Some(budget))
+    }
+
+    fn import_block(&mut self, block: Block, budget: Option<Duration>) -> Result<(), String> {
 
         let is_genesis = self.len() == 0; 
 
@@ -101,19 +415,69 @@ impl Blockchain {
             return Err("The new block has to point to the previous block".into());
         }
 
+        if !is_genesis && block.timestamp <= self.median_time_past() {
+            return Err("The block timestamp is not after the median of the previous blocks".into());
+        }
+
+        if block.timestamp > unix_time_now().saturating_add(self.max_future_drift) {
+            return Err("The block timestamp is too far in the future".into());
+        }
+
+        self.consensus.validate_header(self, &block)?;
+
         let old_state = self.accounts.clone();
+        let old_subscriptions = self.subscriptions.clone();
+        let old_oracle_reports = self.oracle_reports.clone();
+
+        let started = Instant::now();
+        let mut execution_time = ExecutionTime::default();
+
+        if is_genesis {
+            self.create_system_accounts();
+        }
 
         for(i,transaction) in block.transactions.iter().enumerate() {
             
-            if let Err(err) = transaction.execute(self, &is_genesis) {
+            let transaction_started = Instant::now();
+            let result = transaction.execute(self, &is_genesis);
+            execution_time.transactions.push(transaction_started.elapsed());
+
+            if let Err(err) = result {
                 self.accounts = old_state;
+                self.subscriptions = old_subscriptions;
+                self.oracle_reports = old_oracle_reports;
 
                 return Err(format!("Error {} {} ",i+1,err));
                 
             }
         }
 
-        self.blocks.push(block);
+        self.pay_subscriptions();
+
+        let consensus = self.consensus.clone();
+        if let Err(err) = consensus.finalize_block(self, &block) {
+            self.accounts = old_state;
+            self.subscriptions = old_subscriptions;
+            self.oracle_reports = old_oracle_reports;
+
+            return Err(err);
+        }
+
+        execution_time.total = started.elapsed();
+        if let Some(budget) = budget {
+            if execution_time.total > budget {
+                self.accounts = old_state;
+                self.subscriptions = old_subscriptions;
+                self.oracle_reports = old_oracle_reports;
+
+                return Err(format!("The block took {:?} to execute, over the {:?} budget", execution_time.total, budget));
+            }
+        }
+
+        let included: Vec<Vec<u8>> = block.transactions.iter().map(|t| t.calculate_hash()).collect();
+        self.pending_transactions.retain(|t| !included.contains(&t.calculate_hash()));
+
+        self.blocks.push(SealedBlock{block, execution_time});
 
         Ok(())
 
@@ -123,6 +487,15 @@ impl Blockchain {
         self.blocks.len()
     }
 
+    pub fn blocks(&self) -> &[SealedBlock] {
+        &self.blocks
+    }
+
+    /// Accounts by id. They only change through transactions.
+    pub fn accounts(&self) -> &HashMap<String, Account> {
+        &self.accounts
+    }
+
     pub fn get_last_block_hash(&self) -> Option<String> {
         if self.len() == 0 {
             return None; 
@@ -130,6 +503,170 @@ impl Blockchain {
         self.blocks[self.len()-1].hash.clone()
     }
 
+    pub fn set_max_future_drift(&mut self, seconds: u64) {
+        self.max_future_drift = seconds;
+    }
+
+    pub fn set_dust_rules(&mut self, dust_rules: DustRules) {
+        self.dust_rules = dust_rules;
+    }
+
+    /// Median timestamp of the last MEDIAN_TIME_SPAN blocks, 0 for an empty chain
+    pub fn median_time_past(&self) -> u64 {
+        let start = self.len().saturating_sub(MEDIAN_TIME_SPAN);
+        let mut timestamps: Vec<u64> = self.blocks[start..].iter().map(|b| b.timestamp).collect();
+
+        if timestamps.is_empty() {
+            return 0;
+        }
+        timestamps.sort_unstable();
+        timestamps[timestamps.len() / 2]
+    }
+
+    /// Owned copy of every account ordered by id. It doesn't borrow the chain,
+    /// so it stays consistent while further blocks are appended.
+    pub fn accounts_snapshot(&self) -> impl Iterator<Item = (String, AccountView)> {
+        let snapshot: BTreeMap<String, AccountView> = self.accounts.iter().map(|(id, account)| (id.clone(), account.view())).collect();
+        snapshot.into_iter()
+    }
+
+    /// Rebuilds the chain, with the same rules, as it was after its first
+    /// `height` blocks by replaying them from genesis
+    pub fn state_at(&self, height: usize) -> Result<Blockchain, String> {
+        if height > self.len() {
+            return Err("The chain is not that long".into());
+        }
+
+        let mut chain = Blockchain {
+            account_id_rules: self.account_id_rules.clone(),
+            max_future_drift: self.max_future_drift,
+            dust_rules: self.dust_rules.clone(),
+            consensus: self.consensus.clone(),
+            ..Blockchain::new()
+        };
+        for block in self.blocks[..height].iter() {
+            chain.append_block(block.block.clone())?;
+        }
+        Ok(chain)
+    }
+
+    pub fn get_account_view(&self, id: &str) -> Option<AccountView> {
+        self.accounts.get(id).map(|account| account.view())
+    }
+
+    /// Reads `owner`'s store value on behalf of `reader`, honoring store grants
+    pub fn read_store(&self, owner: &str, reader: &str, key: &str) -> Result<Option<&String>, &'static str> {
+        if !self.has_store_access(owner, reader, key, StoreAccess::Read) {
+            return Err("No read access to that store key");
+        }
+        Ok(self.get_store_value(owner, key))
+    }
+
+    /// Median of the values reported for a feed round, the lower one on a tie
+    pub fn oracle_value(&self, feed_id: &str, round: u64) -> Option<u128> {
+        let reports = self.oracle_reports.get(feed_id)?.get(&round)?;
+        let mut values: Vec<u128> = reports.values().copied().collect();
+
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        Some(values[(values.len() - 1) / 2])
+    }
+
+    /// Latest reported round of a feed and its median value
+    pub fn latest_oracle_value(&self, feed_id: &str) -> Option<(u64, u128)> {
+        let (round, _) = self.oracle_reports.get(feed_id)?.iter().next_back()?;
+        self.oracle_value(feed_id, *round).map(|value| (*round, value))
+    }
+
+    pub fn add_pending_transaction(&mut self, transaction: Transaction) {
+        self.pending_transactions.push(transaction);
+    }
+
+    pub fn get_pending_transactions(&self) -> &[Transaction] {
+        &self.pending_transactions
+    }
+
+    pub fn get_pending_transactions_from(&self, from: &str) -> Vec<&Transaction> {
+        self.pending_transactions.iter().filter(|t| t.from == from).collect()
+    }
+
+    /// Removes the pending transaction with the given hash
+    pub fn evict_pending_transaction(&mut self, hash: &[u8]) -> Option<Transaction> {
+        let position = self.pending_transactions.iter().position(|t| t.calculate_hash() == hash)?;
+        self.dropped_transactions.insert(hash.to_vec(), "Evicted from the pending pool".into());
+        Some(self.pending_transactions.remove(position))
+    }
+
+    pub fn flush_pending_transactions(&mut self) -> Vec<Transaction> {
+        for transaction in self.pending_transactions.iter() {
+            self.dropped_transactions.insert(transaction.calculate_hash(), "Pending pool was flushed".into());
+        }
+        std::mem::take(&mut self.pending_transactions)
+    }
+
+    pub fn transaction_status(&self, hash: &[u8]) -> TransactionStatus {
+        for (height, block) in self.blocks.iter().enumerate() {
+            if block.transactions.iter().any(|t| t.calculate_hash() == hash) {
+                return TransactionStatus::Included{height};
+            }
+        }
+
+        if self.pending_transactions.iter().any(|t| t.calculate_hash() == hash) {
+            return TransactionStatus::Pending;
+        }
+
+        match self.dropped_transactions.get(hash) {
+            Some(reason) => TransactionStatus::Dropped{reason: reason.clone()},
+            None => TransactionStatus::Unknown,
+        }
+    }
+
+    /// Number of blocks, counting its own, that include or build on the transaction
+    pub fn confirmations(&self, hash: &[u8]) -> Option<usize> {
+        match self.transaction_status(hash) {
+            TransactionStatus::Included{height} => Some(self.len() - height),
+            _ => None,
+        }
+    }
+
+    /// System accounts skip the id rules, their prefix is reserved for them
+    fn create_system_accounts(&mut self) {
+        for id in SYSTEM_ACCOUNTS.iter() {
+            self.accounts.insert(id.to_string(), Account::new(AccountType::System));
+        }
+    }
+
+    pub fn get_subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// Pays every subscription due at the block being appended. A payment
+    /// that fails pauses its subscription until the payer resumes it.
+    fn pay_subscriptions(&mut self) {
+        let height = self.len() as u64;
+        let mut subscriptions = std::mem::take(&mut self.subscriptions);
+
+        for subscription in subscriptions.iter_mut() {
+            if subscription.next_payment != height {
+                continue;
+            }
+            // A payment that would fall past u64::MAX is never due again.
+            // The first payment can't overflow, create_subscription checks it.
+            subscription.next_payment = subscription.next_payment.saturating_add(subscription.interval_blocks);
+
+            if subscription.paused {
+                continue;
+            }
+            if transfer_tokens(self, &subscription.from, &subscription.to, subscription.amount).is_err() {
+                subscription.paused = true;
+            }
+        }
+
+        self.subscriptions = subscriptions;
+    }
+
 }
 
 
@@ -140,14 +677,50 @@ impl Block {
             hash: None,
             prev_hash,
             transactions: Vec::new(),
+            timestamp: unix_time_now(),
+            author: None,
         }
     }
 
+    pub fn set_timestamp(&mut self, timestamp: u64){
+        self.timestamp = timestamp;
+        self.update_hash();
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn prev_hash(&self) -> Option<&str> {
+        self.prev_hash.as_deref()
+    }
+
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    pub fn nonce(&self) -> u128 {
+        self.nonce
+    }
+
     pub fn set_nonce(&mut self, nonce: u128){
         self.nonce = nonce; 
         self.update_hash();
     }
 
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn set_author(&mut self, author: Option<String>){
+        self.author = author;
+        self.update_hash();
+    }
+
     pub fn calculate_hash(&self) -> Vec<u8> {
         let mut hasher = Blake2b::new();
 
@@ -155,9 +728,14 @@ impl Block {
             hasher.update(transaction.calculate_hash());
         }
 
-        let block_as_string = format!("{:?}", (&self.prev_hash, &self.nonce));
+        let block_as_string = format!("{:?}", (&self.prev_hash, &self.nonce, &self.timestamp));
         hasher.update(&block_as_string);
 
+        // Blocks without an author keep the hash they had before authors existed
+        if let Some(author) = &self.author {
+            hasher.update(format!("{:?}", author));
+        }
+
         return Vec::from(hasher.finalize().as_ref());
     }
 
@@ -183,6 +761,21 @@ impl Block {
 }
 
 
+impl SealedBlock {
+    pub fn execution_time(&self) -> &ExecutionTime {
+        &self.execution_time
+    }
+}
+
+impl Deref for SealedBlock {
+    type Target = Block;
+
+    fn deref(&self) -> &Block {
+        &self.block
+    }
+}
+
+
 impl Transaction {
     pub fn new(from: String, transaction_data: TransactionData, nonce: u128 ) -> Self {
         Transaction{
@@ -191,22 +784,86 @@ impl Transaction {
             record: transaction_data,
             created_at: SystemTime::now(),
             signature: None,
+            memo: None,
         }
     }
 
-    pub fn execute<T: WorldState>(&self, world_state: &mut T, is_initial: &bool ) -> Result <(), &'static str> {
+    pub fn set_memo(&mut self, memo: Vec<u8>) -> Result<(), &'static str> {
+        if memo.len() > MAX_MEMO_SIZE {
+            return Err("Memo is too large");
+        }
+        self.memo = Some(memo);
+        Ok(())
+    }
 
-        if let Some(_account) = world_state.get_account_by_id(&self.from) {
-        } else {
-            if !is_initial {
-                return Err("Account does not exists!");
-            }
+    pub fn memo(&self) -> Option<&[u8]> {
+        self.memo.as_deref()
+    }
+
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn nonce(&self) -> u128 {
+        self.nonce
+    }
+
+    pub fn created_at(&self) -> SystemTime {
+        self.created_at
+    }
+
+    /// Time since the transaction was created, zero if the clock went backwards
+    pub fn age(&self) -> Duration {
+        self.created_at.elapsed().unwrap_or_default()
+    }
+
+    /// Validates the transaction against the current state, then applies it
+    pub fn execute<T: WorldStateWriter>(&self, world_state: &mut T, is_initial: &bool ) -> Result <(), TransactionError> {
+        self.validate(world_state, is_initial)?;
+        self.apply(world_state, is_initial)
+    }
+
+    /// Checks whether the transaction could be applied, without changing the state
+    pub fn validate<T: WorldStateReader>(&self, world_state: &T, is_initial: &bool ) -> Result <(), &'static str> {
+
+        if is_system_account(&self.from) {
+            return Err("System accounts can't send transactions");
         }
 
-        return match &self.record {
+        if !world_state.account_exists(&self.from) && !is_initial {
+            return Err("Account does not exists!");
+        }
+
+        match &self.record {
 
             TransactionData::CreateUserAccount(account) => {
-                world_state.create_account (account.into(),  AccountType::User)
+                world_state.account_id_rules().validate(account)?;
+                if world_state.account_exists(account) {
+                    return Err("User exists!");
+                }
+                Ok(())
+            }
+
+            TransactionData::CreateOracleAccount(account) => {
+                if !is_initial {
+                    return Err("Oracles can only be registered on initial creation");
+                }
+                world_state.account_id_rules().validate(account)?;
+                if world_state.account_exists(account) {
+                    return Err("User exists!");
+                }
+                Ok(())
+            }
+
+            TransactionData::PublishOracleValue {feed_id, round, ..} => {
+                match world_state.get_account_by_id(&self.from) {
+                    Some(account) if matches!(account.acc_type, AccountType::Oracle) => {}
+                    _ => return Err("Only registered oracles can publish values"),
+                }
+                if world_state.get_oracle_report(feed_id, *round, &self.from).is_some() {
+                    return Err("Oracle already reported for this round");
+                }
+                Ok(())
             }
 
             TransactionData::CreateTokens {receiver, amount } => {
@@ -215,50 +872,159 @@ impl Transaction {
                     return Err("Token creation is only ava. on initial creation");
                 }
 
-                return if let Some(account) = world_state.get_account_by_id_mut(receiver){
-                    account.tokens += *amount;
-                    Ok(())
-                }else{
-                    Err("Receiver Account does not exists")
-                };
+                match world_state.get_balance(receiver) {
+                    Some(tokens) if tokens.checked_add(*amount).is_some() => Ok(()),
+                    Some(_) => Err("Averspent or Arithmetic error"),
+                    None => Err("Receiver Account does not exists"),
+                }
             }
 
             TransactionData::TransferTokens { to, amount } => {
-                let recv_tokens: u128; 
-                let sender_tokens: u128;
+                check_transfer(world_state, &self.from, to, *amount).map(|_| ())
+            }
 
-                if let Some(recv) = world_state.get_account_by_id_mut(to) {
-                    recv_tokens = recv.tokens;
-                }else{
+            TransactionData::CreateSubscription { to, interval_blocks, .. } => {
+                if !world_state.account_exists(to) {
                     return Err("Receiver Account does not exists!");
                 }
+                if *interval_blocks == 0 {
+                    return Err("Subscription interval must be at least one block");
+                }
+                if world_state.height().checked_add(*interval_blocks).is_none() {
+                    return Err("Subscription interval is too long");
+                }
+                if world_state.get_subscription(&self.from, to).is_some() {
+                    return Err("Subscription exists!");
+                }
+                Ok(())
+            }
 
-                if let Some(sender) = world_state.get_account_by_id_mut(&self.from) {
-                    sender_tokens = sender.tokens;
-                }else{
-                    return Err("That account does not exists");
+            TransactionData::CancelSubscription { to } | TransactionData::ResumeSubscription { to } => {
+                if world_state.get_subscription(&self.from, to).is_none() {
+                    return Err("Subscription does not exists");
+                }
+                Ok(())
+            }
+            
+            TransactionData::ChangeStoreValue { .. } => {
+                Ok(())
+            }
+
+            TransactionData::ChangeSharedStoreValue { owner, key, .. } => {
+                if !world_state.account_exists(owner) {
+                    return Err("Store owner does not exists");
+                }
+                if !world_state.has_store_access(owner, &self.from, key, StoreAccess::Write) {
+                    return Err("No write access to that store key");
                 }
+                Ok(())
+            }
 
-                let balance_recv_new = recv_tokens.checked_add(*amount);
-                let balance_sender_new = sender_tokens.checked_sub(*amount);
+            TransactionData::GrantStoreAccess { grantee, .. } => {
+                if !world_state.account_exists(grantee) {
+                    return Err("Grantee Account does not exists");
+                }
+                if grantee == &self.from {
+                    return Err("Accounts already have access to their own store");
+                }
+                Ok(())
+            }
 
-                if balance_recv_new.is_some() && balance_sender_new.is_some() {
-                    //missing logic
-                    return Ok(());
-                } else {
-                    return Err("Averspent or Arithmetic error");
+            TransactionData::RevokeStoreAccess { grantee, prefix } => {
+                let granted = world_state.get_account_by_id(&self.from)
+                    .map(|account| account.store_grants.iter().any(|g| &g.grantee == grantee && &g.prefix == prefix))
+                    .unwrap_or(false);
+                if !granted {
+                    return Err("Store access was not granted");
                 }
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies an already validated transaction
+    fn apply<T: WorldStateWriter>(&self, world_state: &mut T, _is_initial: &bool ) -> Result <(), TransactionError> {
+
+        return match &self.record {
+
+            TransactionData::CreateUserAccount(account) => {
+                world_state.create_account (account.into(),  AccountType::User).map_err(TransactionError::from)
+            }
+
+            TransactionData::CreateOracleAccount(account) => {
+                world_state.create_account (account.into(),  AccountType::Oracle).map_err(TransactionError::from)
+            }
+
+            TransactionData::PublishOracleValue {feed_id, value, round} => {
+                world_state.publish_oracle_value(feed_id.clone(), *round, self.from.clone(), *value).map_err(TransactionError::from)
+            }
+
+            TransactionData::CreateTokens {receiver, amount } => {
+                return if let Some(account) = world_state.get_account_by_id_mut(receiver){
+                    account.tokens = audited_add(receiver, account.tokens, *amount)?;
+                    Ok(())
+                }else{
+                    Err("Receiver Account does not exists".into())
+                };
+            }
+
+            TransactionData::TransferTokens { to, amount } => {
+                transfer_tokens(world_state, &self.from, to, *amount)
+            }
+
+            TransactionData::CreateSubscription { to, amount, interval_blocks } => {
+                world_state.create_subscription(self.from.clone(), to.clone(), *amount, *interval_blocks).map_err(TransactionError::from)
+            }
+
+            TransactionData::CancelSubscription { to } => {
+                world_state.cancel_subscription(&self.from, to).map_err(TransactionError::from)
+            }
+
+            TransactionData::ResumeSubscription { to } => {
+                return if let Some(subscription) = world_state.get_subscription_mut(&self.from, to) {
+                    subscription.paused = false;
+                    Ok(())
+                } else {
+                    Err("Subscription does not exists".into())
+                };
             }
             
-            _ => {
-                Err("Unknown transaction")
+            TransactionData::ChangeStoreValue { key, value } => {
+                set_store_value(world_state, &self.from, key, value).map_err(TransactionError::from)
+            }
+
+            TransactionData::ChangeSharedStoreValue { owner, key, value } => {
+                set_store_value(world_state, owner, key, value).map_err(TransactionError::from)
+            }
+
+            TransactionData::GrantStoreAccess { grantee, prefix, access } => {
+                return if let Some(account) = world_state.get_account_by_id_mut(&self.from) {
+                    account.store_grants.retain(|g| !(&g.grantee == grantee && &g.prefix == prefix));
+                    account.store_grants.push(StoreGrant{
+                        grantee: grantee.clone(),
+                        prefix: prefix.clone(),
+                        access: *access,
+                    });
+                    Ok(())
+                } else {
+                    Err("That account does not exists".into())
+                };
+            }
+
+            TransactionData::RevokeStoreAccess { grantee, prefix } => {
+                return if let Some(account) = world_state.get_account_by_id_mut(&self.from) {
+                    account.store_grants.retain(|g| !(&g.grantee == grantee && &g.prefix == prefix));
+                    Ok(())
+                } else {
+                    Err("That account does not exists".into())
+                };
             }
         };
     }
 
     pub fn calculate_hash(&self) -> Vec<u8> {
         let mut hasher = Blake2b::new();
-        let transaction_as_string = format!("{:?}", (&self.created_at, &self.record, &self.from, &self.nonce));
+        let transaction_as_string = format!("{:?}", (&self.created_at, &self.record, &self.from, &self.nonce, &self.memo));
         hasher.update(&transaction_as_string);
         return Vec::from(hasher.finalize().as_ref());
     }
@@ -275,20 +1041,78 @@ impl Transaction {
     }
 }
 
-impl WorldState for Blockchain {
+impl From<&'static str> for TransactionError {
+    fn from(reason: &'static str) -> Self {
+        TransactionError::Rejected(reason)
+    }
+}
+
+impl From<BalanceError> for TransactionError {
+    fn from(error: BalanceError) -> Self {
+        TransactionError::Balance(error)
+    }
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionError::Rejected(reason) => write!(f, "{}", reason),
+            TransactionError::Balance(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BalanceError::Overflow{account, balance, amount} => write!(f, "Balance overflow for {}: {} + {}", account, balance, amount),
+            BalanceError::Underflow{account, balance, amount} => write!(f, "Balance underflow for {}: {} - {}", account, balance, amount),
+        }
+    }
+}
+
+impl std::error::Error for BalanceError {}
+
+impl WorldStateReader for Blockchain {
     fn get_user_ids(&self) -> Vec<String> {
         self.accounts.keys().map(|s| s.clone()).collect()
     }
 
-    fn get_account_by_id_mut(&mut self, id: &String) -> Option<&mut Account>{
-        self.accounts.get_mut(id)
+    fn get_account_by_id(& self, id: &str) -> Option<& Account> {
+        self.accounts.get(id)
     }
 
-    fn get_account_by_id(& self, id: &String) -> Option<& Account> {
-        self.accounts.get(id)
+    fn get_subscription(&self, from: &str, to: &str) -> Option<&Subscription> {
+        self.subscriptions.iter().find(|s| s.from == from && s.to == to)
+    }
+
+    fn get_oracle_report(&self, feed_id: &str, round: u64, reporter: &str) -> Option<u128> {
+        self.oracle_reports.get(feed_id)?.get(&round)?.get(reporter).copied()
+    }
+
+    fn height(&self) -> u64 {
+        self.len() as u64
+    }
+
+    fn dust_rules(&self) -> DustRules {
+        self.dust_rules.clone()
+    }
+
+    fn account_id_rules(&self) -> AccountIdRules {
+        self.account_id_rules.clone()
+    }
+}
+
+impl WorldStateWriter for Blockchain {
+    fn get_account_by_id_mut(&mut self, id: &str) -> Option<&mut Account>{
+        self.accounts.get_mut(id)
     }
 
     fn create_account(&mut self, id: String, account_type: AccountType) -> Result<(), &'static str> {
+        self.account_id_rules.validate(&id)?;
+
         return if !self.get_user_ids().contains(&id) {
             let acc = Account::new(account_type);
             self.accounts.insert(id,acc);
@@ -297,6 +1121,49 @@ impl WorldState for Blockchain {
             Err("User exists!")
         };
     }
+
+    fn create_subscription(&mut self, from: String, to: String, amount: Amount, interval_blocks: u64) -> Result<(), &'static str> {
+        if interval_blocks == 0 {
+            return Err("Subscription interval must be at least one block");
+        }
+        if self.get_subscription_mut(&from, &to).is_some() {
+            return Err("Subscription exists!");
+        }
+
+        let next_payment = self.height().checked_add(interval_blocks).ok_or("Subscription interval is too long")?;
+        self.subscriptions.push(Subscription{
+            from,
+            to,
+            amount,
+            interval_blocks,
+            next_payment,
+            paused: false,
+        });
+        Ok(())
+    }
+
+    fn get_subscription_mut(&mut self, from: &str, to: &str) -> Option<&mut Subscription> {
+        self.subscriptions.iter_mut().find(|s| s.from == from && s.to == to)
+    }
+
+    fn cancel_subscription(&mut self, from: &str, to: &str) -> Result<(), &'static str> {
+        let count = self.subscriptions.len();
+        self.subscriptions.retain(|s| !(s.from == from && s.to == to));
+
+        if self.subscriptions.len() == count {
+            return Err("Subscription does not exists");
+        }
+        Ok(())
+    }
+
+    fn publish_oracle_value(&mut self, feed_id: String, round: u64, reporter: String, value: u128) -> Result<(), &'static str> {
+        let reports = self.oracle_reports.entry(feed_id).or_default().entry(round).or_default();
+        if reports.contains_key(&reporter) {
+            return Err("Oracle already reported for this round");
+        }
+        reports.insert(reporter, value);
+        Ok(())
+    }
 }
 
 
@@ -306,15 +1173,328 @@ impl WorldState for Blockchain {
 impl Account {
     pub fn new(account_type: AccountType) -> Self {
         return Self{
-            tokens: 0, 
+            tokens: Amount::default(), 
             acc_type: account_type, 
-            store: HashMap::new()
+            store: BTreeMap::new(),
+            store_grants: Vec::new(),
+        }
+    }
+
+    pub fn balance(&self) -> Amount {
+        self.tokens
+    }
+
+    pub fn account_type(&self) -> &AccountType {
+        &self.acc_type
+    }
+
+    pub fn store_get(&self, key: &str) -> Option<&str> {
+        self.store.get(key).map(|value| value.as_str())
+    }
+
+    /// Entries whose key starts with `prefix`, in key order
+    pub fn store_scan<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        scan_prefix(&self.store, prefix)
+    }
+
+    pub fn store_grants(&self) -> &[StoreGrant] {
+        &self.store_grants
+    }
+
+    pub fn view(&self) -> AccountView {
+        AccountView{
+            store: self.store.clone(),
+            acc_type: self.acc_type.clone(),
+            tokens: self.tokens,
+        }
+    }
+
+}
+
+
+impl AccountView {
+    pub fn balance(&self) -> Amount {
+        self.tokens
+    }
+
+    pub fn account_type(&self) -> &AccountType {
+        &self.acc_type
+    }
+
+    pub fn store_get(&self, key: &str) -> Option<&str> {
+        self.store.get(key).map(|value| value.as_str())
+    }
+
+    /// Entries whose key starts with `prefix`, in key order
+    pub fn store_scan<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        scan_prefix(&self.store, prefix)
+    }
+}
+
+
+impl AccountIdRules {
+    pub fn validate(&self, id: &str) -> Result<(), &'static str> {
+        let length = id.chars().count();
+        if length < self.min_length {
+            return Err("Account id is too short");
+        }
+        if length > self.max_length {
+            return Err("Account id is too long");
+        }
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || self.extra_chars.contains(c)) {
+            return Err("Account id contains invalid characters");
+        }
+        if self.reserved_prefixes.iter().any(|prefix| id.starts_with(prefix.as_str())) {
+            return Err("Account id uses a reserved prefix");
+        }
+        Ok(())
+    }
+}
+
+impl Default for AccountIdRules {
+    /// ASCII only, so look-alike unicode characters can't spoof other ids
+    fn default() -> Self {
+        AccountIdRules{
+            min_length: 1,
+            max_length: 64,
+            extra_chars: "_-.".into(),
+            reserved_prefixes: vec!["system:".into()],
+        }
+    }
+}
+
+
+impl Subscription {
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    pub fn interval_blocks(&self) -> u64 {
+        self.interval_blocks
+    }
+
+    pub fn next_payment(&self) -> u64 {
+        self.next_payment
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+
+pub fn is_system_account(id: &str) -> bool {
+    SYSTEM_ACCOUNTS.contains(&id)
+}
+
+
+/// Amount that would actually move if the transfer is possible. With dust
+/// sweeping on it can be the sender's whole balance.
+fn check_transfer<T: WorldStateReader>(world_state: &T, from: &str, to: &str, amount: Amount) -> Result<Amount, &'static str> {
+    let rules = world_state.dust_rules();
+
+    let recv_tokens = match world_state.get_balance(to) {
+        Some(tokens) => tokens,
+        None => return Err("Receiver Account does not exists!"),
+    };
+
+    let sender_tokens = match world_state.get_balance(from) {
+        Some(tokens) => tokens,
+        None => return Err("That account does not exists"),
+    };
+
+    if amount < rules.min_transfer {
+        return Err("Transfer is below the minimum amount");
+    }
+
+    let mut amount = amount;
+    let remaining = match sender_tokens.checked_sub(amount) {
+        Some(remaining) => remaining,
+        None => return Err("Averspent or Arithmetic error"),
+    };
+    if remaining != Amount::default() && remaining < rules.min_balance {
+        if !rules.sweep_dust {
+            return Err("Transfer would leave a dust balance");
         }
+        amount = sender_tokens;
+    }
+
+    if from == to {
+        return Ok(amount);
+    }
+
+    match recv_tokens.checked_add(amount) {
+        Some(recv_new) if recv_new != Amount::default() && recv_new < rules.min_balance => Err("Transfer would leave the receiver with a dust balance"),
+        Some(_) => Ok(amount),
+        None => Err("Averspent or Arithmetic error"),
+    }
+}
+
+fn transfer_tokens<T: WorldStateWriter>(world_state: &mut T, from: &str, to: &str, amount: Amount) -> Result<(), TransactionError> {
+    let amount = check_transfer(world_state, from, to, amount)?;
+    if from == to {
+        return Ok(());
+    }
+
+    let sender = world_state.get_account_by_id_mut(from).unwrap();
+    sender.tokens = audited_sub(from, sender.tokens, amount)?;
+    let recv = world_state.get_account_by_id_mut(to).unwrap();
+    recv.tokens = audited_add(to, recv.tokens, amount)?;
+    Ok(())
+}
+
+
+/// Balance addition for the apply step. Validation should already have ruled
+/// out overflow, so getting here is a bug. It is checked in every build and
+/// fails the transaction instead of wrapping the balance.
+fn audited_add(account: &str, balance: Amount, amount: Amount) -> Result<Amount, BalanceError> {
+    balance.checked_add(amount).ok_or_else(|| BalanceError::Overflow {
+        account: account.to_string(),
+        balance,
+        amount,
+    })
+}
+
+/// Balance subtraction for the apply step, see audited_add
+fn audited_sub(account: &str, balance: Amount, amount: Amount) -> Result<Amount, BalanceError> {
+    balance.checked_sub(amount).ok_or_else(|| BalanceError::Underflow {
+        account: account.to_string(),
+        balance,
+        amount,
+    })
+}
+
+
+fn set_store_value<T: WorldStateWriter>(world_state: &mut T, owner: &str, key: &str, value: &str) -> Result<(), &'static str> {
+    if let Some(account) = world_state.get_account_by_id_mut(owner) {
+        account.store.insert(key.to_string(), value.to_string());
+        Ok(())
+    } else {
+        Err("Store owner does not exists")
     }
+}
+
 
+fn scan_prefix<'a>(store: &'a BTreeMap<String, String>, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+    store.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+}
+
+
+/// Seconds since the unix epoch
+fn unix_time_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
 }
 
 
 fn byte_vector_to_string(arr: &Vec<u8>) -> String {
     arr.iter().map(|&c| c as char).collect()
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dev::{dev_account, DevChain};
+
+    fn subscribe(interval_blocks: u64) -> Transaction {
+        let record = TransactionData::CreateSubscription{to: dev_account(1), amount: Amount::from(1u64), interval_blocks};
+        Transaction::new(dev_account(0), record, 0)
+    }
+
+    #[test]
+    fn a_subscription_due_past_the_last_height_is_rejected() {
+        let mut dev = DevChain::new(2);
+        let height = dev.chain().height();
+        assert_eq!(height, 1);
+
+        assert_eq!(subscribe(u64::MAX).validate(dev.chain(), &false), Err("Subscription interval is too long"));
+        assert!(dev.submit(subscribe(u64::MAX)).is_err());
+        assert!(dev.chain().get_subscriptions().is_empty());
+
+        // applying it without validation fails the same way
+        let mut chain = dev.chain().clone();
+        assert_eq!(chain.create_subscription(dev_account(0), dev_account(1), Amount::from(1u64), u64::MAX), Err("Subscription interval is too long"));
+
+        dev.submit(subscribe(u64::MAX - height)).unwrap();
+        assert_eq!(dev.chain().get_subscriptions().len(), 1);
+    }
+
+    #[test]
+    fn the_last_payable_height_stops_the_schedule() {
+        let dev = DevChain::new(2);
+        let mut chain = dev.chain().clone();
+        let balance = chain.get_balance(&dev_account(1)).unwrap();
+        chain.subscriptions.push(Subscription{
+            from: dev_account(0),
+            to: dev_account(1),
+            amount: Amount::from(5u64),
+            interval_blocks: u64::MAX,
+            next_payment: chain.height(),
+            paused: false
+        });
+
+        let mut block = Block::new(chain.get_last_block_hash());
+        block.set_timestamp(dev.now() + 1);
+        chain.author_block(&mut block).unwrap();
+        chain.append_block(block).unwrap();
+
+        assert_eq!(chain.get_balance(&dev_account(1)), balance.checked_add(Amount::from(5u64)));
+        assert_eq!(chain.get_subscriptions()[0].next_payment, u64::MAX);
+    }
+
+    #[test]
+    fn an_invalid_balance_update_names_the_account_and_amounts() {
+        let dev = DevChain::new(2);
+        let mut chain = dev.chain().clone();
+        chain.get_account_by_id_mut(&dev_account(1)).unwrap().tokens = Amount::MAX;
+        let one = Amount::from(1u64);
+
+        // applied without validation, as if validation had missed it
+        let mint = Transaction::new(dev_account(0), TransactionData::CreateTokens{receiver: dev_account(1), amount: one}, 0);
+        let overflow = BalanceError::Overflow{account: dev_account(1), balance: Amount::MAX, amount: one};
+        assert_eq!(mint.apply(&mut chain, &false), Err(TransactionError::Balance(overflow.clone())));
+        assert_eq!(chain.get_balance(&dev_account(1)), Some(Amount::MAX));
+        assert_eq!(overflow.to_string(), format!("Balance overflow for {}: {} + 1", dev_account(1), Amount::MAX));
+
+        let underflow = BalanceError::Underflow{account: "alice".into(), balance: one, amount: Amount::from(2u64)};
+        assert_eq!(audited_sub("alice", one, Amount::from(2u64)), Err(underflow));
+        assert_eq!(audited_add("alice", one, one), Ok(Amount::from(2u64)));
+    }
+
+    #[test]
+    fn validate_checks_new_account_ids_against_the_chain_rules() {
+        let rules = AccountIdRules{max_length: 8, extra_chars: ":".into(), ..AccountIdRules::default()};
+        let chain = Blockchain::with_account_id_rules(rules);
+        let create = |id: &str| Transaction::new("genesis".into(), TransactionData::CreateUserAccount(id.into()), 0);
+
+        assert_eq!(create("alice").validate(&chain, &true), Ok(()));
+        assert_eq!(create("").validate(&chain, &true), Err("Account id is too short"));
+        assert_eq!(create("aliceandbob").validate(&chain, &true), Err("Account id is too long"));
+        assert_eq!(create("al ice").validate(&chain, &true), Err("Account id contains invalid characters"));
+        assert_eq!(create("system:x").validate(&chain, &true), Err("Account id uses a reserved prefix"));
+
+        let oracle = Transaction::new("genesis".into(), TransactionData::CreateOracleAccount("pri ce".into()), 0);
+        assert_eq!(oracle.validate(&chain, &true), Err("Account id contains invalid characters"));
+    }
+
+    #[test]
+    fn an_empty_transfer_to_an_empty_account_is_not_dust() {
+        let mut chain = DevChain::new(1).chain().clone();
+        chain.set_dust_rules(DustRules{min_balance: Amount::from(10u64), ..DustRules::default()});
+        chain.create_account("empty".into(), AccountType::User).unwrap();
+
+        assert_eq!(check_transfer(&chain, &dev_account(0), "empty", Amount::default()), Ok(Amount::default()));
+        assert_eq!(check_transfer(&chain, &dev_account(0), "empty", Amount::from(5u64)), Err("Transfer would leave the receiver with a dust balance"));
+        assert_eq!(check_transfer(&chain, &dev_account(0), "empty", Amount::from(10u64)), Ok(Amount::from(10u64)));
+    }
+}
@@ -0,0 +1,93 @@
+//! Heap usage estimates of the chain state, so its growth can be tracked
+
+use linked::MemoryFootprint;
+
+use crate::*;
+
+
+/// The consensus engine is shared behind an `Arc` and isn't counted
+impl MemoryFootprint for Blockchain {
+    fn heap_size(&self) -> usize {
+        self.blocks.heap_size()
+            + self.accounts.heap_size()
+            + self.pending_transactions.heap_size()
+            + self.dropped_transactions.heap_size()
+            + self.subscriptions.heap_size()
+            + self.oracle_reports.heap_size()
+            + self.account_id_rules.heap_size()
+    }
+}
+
+impl MemoryFootprint for SealedBlock {
+    fn heap_size(&self) -> usize {
+        self.block.heap_size() + self.execution_time.heap_size()
+    }
+}
+
+impl MemoryFootprint for Block {
+    fn heap_size(&self) -> usize {
+        self.transactions.heap_size() + self.prev_hash.heap_size() + self.hash.heap_size()
+    }
+}
+
+impl MemoryFootprint for ExecutionTime {
+    fn heap_size(&self) -> usize {
+        self.transactions.heap_size()
+    }
+}
+
+impl MemoryFootprint for Transaction {
+    fn heap_size(&self) -> usize {
+        self.from.heap_size() + self.record.heap_size() + self.signature.heap_size() + self.memo.heap_size()
+    }
+}
+
+impl MemoryFootprint for TransactionData {
+    fn heap_size(&self) -> usize {
+        match self {
+            TransactionData::CreateUserAccount(id) | TransactionData::CreateOracleAccount(id) => id.heap_size(),
+            TransactionData::ChangeStoreValue { key, value } => key.heap_size() + value.heap_size(),
+            TransactionData::ChangeSharedStoreValue { owner, key, value } => {
+                owner.heap_size() + key.heap_size() + value.heap_size()
+            }
+            TransactionData::GrantStoreAccess { grantee, prefix, .. }
+            | TransactionData::RevokeStoreAccess { grantee, prefix } => grantee.heap_size() + prefix.heap_size(),
+            TransactionData::TransferTokens { to, .. }
+            | TransactionData::CreateSubscription { to, .. }
+            | TransactionData::CancelSubscription { to }
+            | TransactionData::ResumeSubscription { to } => to.heap_size(),
+            TransactionData::CreateTokens { receiver, .. } => receiver.heap_size(),
+            TransactionData::PublishOracleValue { feed_id, .. } => feed_id.heap_size(),
+        }
+    }
+}
+
+impl MemoryFootprint for Subscription {
+    fn heap_size(&self) -> usize {
+        self.from.heap_size() + self.to.heap_size()
+    }
+}
+
+impl MemoryFootprint for Account {
+    fn heap_size(&self) -> usize {
+        self.store.heap_size() + self.store_grants.heap_size()
+    }
+}
+
+impl MemoryFootprint for AccountView {
+    fn heap_size(&self) -> usize {
+        self.store.heap_size()
+    }
+}
+
+impl MemoryFootprint for StoreGrant {
+    fn heap_size(&self) -> usize {
+        self.grantee.heap_size() + self.prefix.heap_size()
+    }
+}
+
+impl MemoryFootprint for AccountIdRules {
+    fn heap_size(&self) -> usize {
+        self.extra_chars.heap_size() + self.reserved_prefixes.heap_size()
+    }
+}
@@ -0,0 +1,255 @@
+//! 256 bit unsigned integer used for token amounts with the `u256` feature
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Unsigned 256 bit integer, stored as little endian 64 bit limbs
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    pub fn checked_add(self, other: U256) -> Option<U256> {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        if carry {
+            return None;
+        }
+        Some(U256(result))
+    }
+
+    pub fn checked_sub(self, other: U256) -> Option<U256> {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        if borrow {
+            return None;
+        }
+        Some(U256(result))
+    }
+
+    pub fn checked_mul(self, other: U256) -> Option<U256> {
+        let mut result = [0u64; 8];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let current = result[i + j] as u128 + self.0[i] as u128 * other.0[j] as u128 + carry;
+                result[i + j] = current as u64;
+                carry = current >> 64;
+            }
+            result[i + 4] = carry as u64;
+        }
+        if result[4..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        Some(U256([result[0], result[1], result[2], result[3]]))
+    }
+
+    /// Quotient and remainder of a division by a 64 bit divisor
+    pub fn div_rem_u64(self, divisor: u64) -> Option<(U256, u64)> {
+        if divisor == 0 {
+            return None;
+        }
+        let mut quotient = [0u64; 4];
+        let mut remainder: u128 = 0;
+        for i in (0..4).rev() {
+            let current = (remainder << 64) | self.0[i] as u128;
+            quotient[i] = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
+        }
+        Some((U256(quotient), remainder as u64))
+    }
+
+    /// Parses a hex string, with or without a `0x` prefix
+    pub fn from_hex(hex: &str) -> Result<U256, &'static str> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        if digits.is_empty() {
+            return Err("Empty hex amount");
+        }
+
+        let mut value = U256::ZERO;
+        for c in digits.chars() {
+            let digit = match c.to_digit(16) {
+                Some(digit) => digit,
+                None => return Err("Invalid hex digit in amount"),
+            };
+            value = value.checked_mul(U256::from(16u64))
+                .and_then(|v| v.checked_add(U256::from(digit as u64)))
+                .ok_or("Hex amount does not fit in 256 bits")?;
+        }
+        Ok(value)
+    }
+
+    /// Parses a decimal string
+    pub fn from_dec(dec: &str) -> Result<U256, &'static str> {
+        if dec.is_empty() {
+            return Err("Empty amount");
+        }
+
+        let mut value = U256::ZERO;
+        for c in dec.chars() {
+            let digit = match c.to_digit(10) {
+                Some(digit) => digit,
+                None => return Err("Invalid digit in amount"),
+            };
+            value = value.checked_mul(U256::from(10u64))
+                .and_then(|v| v.checked_add(U256::from(digit as u64)))
+                .ok_or("Amount does not fit in 256 bits")?;
+        }
+        Ok(value)
+    }
+}
+
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+
+        let mut digits = Vec::new();
+        let mut value = *self;
+        while !value.is_zero() {
+            let (quotient, remainder) = value.div_rem_u64(10).unwrap();
+            digits.push(b'0' + remainder as u8);
+            value = quotient;
+        }
+        digits.reverse();
+        f.pad_integral(true, "", std::str::from_utf8(&digits).unwrap())
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut hex = String::new();
+        for limb in self.0.iter().rev() {
+            if hex.is_empty() {
+                if *limb != 0 {
+                    hex = format!("{:x}", limb);
+                }
+            } else {
+                hex.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if hex.is_empty() {
+            hex.push('0');
+        }
+        f.pad_integral(true, "0x", &hex)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_DEC: &str = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+    #[test]
+    fn checked_add_overflows_past_max() {
+        assert_eq!(U256::MAX.checked_add(U256::ZERO), Some(U256::MAX));
+        assert_eq!(U256::MAX.checked_add(U256::from(1u64)), None);
+        assert_eq!(U256::MAX.checked_add(U256::MAX), None);
+        // the carry has to ripple through every limb
+        assert_eq!(U256([u64::MAX, u64::MAX, u64::MAX, 0]).checked_add(U256::from(1u64)), Some(U256([0, 0, 0, 1])));
+    }
+
+    #[test]
+    fn checked_sub_underflows_below_zero() {
+        assert_eq!(U256::ZERO.checked_sub(U256::ZERO), Some(U256::ZERO));
+        assert_eq!(U256::ZERO.checked_sub(U256::from(1u64)), None);
+        assert_eq!(U256::MAX.checked_sub(U256::MAX), Some(U256::ZERO));
+        assert_eq!(U256([0, 0, 0, 1]).checked_sub(U256::from(1u64)), Some(U256([u64::MAX, u64::MAX, u64::MAX, 0])));
+    }
+
+    #[test]
+    fn checked_mul_overflows_past_max() {
+        assert_eq!(U256::MAX.checked_mul(U256::ZERO), Some(U256::ZERO));
+        assert_eq!(U256::ZERO.checked_mul(U256::MAX), Some(U256::ZERO));
+        assert_eq!(U256::MAX.checked_mul(U256::from(1u64)), Some(U256::MAX));
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u64)), None);
+        assert_eq!(U256([0, 0, 1, 0]).checked_mul(U256([0, 0, 1, 0])), None);
+        assert_eq!(U256([0, 1, 0, 0]).checked_mul(U256([0, 0, 1, 0])), Some(U256([0, 0, 0, 1])));
+    }
+
+    #[test]
+    fn from_hex_rejects_more_than_256_bits() {
+        let max = "f".repeat(64);
+        assert_eq!(U256::from_hex(&max), Ok(U256::MAX));
+        assert_eq!(U256::from_hex(&format!("0x{}", max)), Ok(U256::MAX));
+        assert!(U256::from_hex(&format!("1{}", "0".repeat(64))).is_err());
+        assert!(U256::from_hex(&"f".repeat(65)).is_err());
+        assert!(U256::from_hex("").is_err());
+        assert!(U256::from_hex("0x").is_err());
+        assert!(U256::from_hex("0xg").is_err());
+    }
+
+    #[test]
+    fn from_dec_rejects_more_than_256_bits() {
+        assert_eq!(U256::from_dec(MAX_DEC), Ok(U256::MAX));
+        // MAX + 1, and an extra digit
+        assert!(U256::from_dec("115792089237316195423570985008687907853269984665640564039457584007913129639936").is_err());
+        assert!(U256::from_dec(&format!("{}0", MAX_DEC)).is_err());
+        assert!(U256::from_dec("").is_err());
+        assert!(U256::from_dec("12a").is_err());
+    }
+
+    #[test]
+    fn display_and_hex_round_trip() {
+        assert_eq!(U256::MAX.to_string(), MAX_DEC);
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(format!("{:x}", U256::MAX), "f".repeat(64));
+        assert_eq!(format!("{:#x}", U256([0, 1, 0, 0])), "0x10000000000000000");
+        let value = U256::from(u128::MAX).checked_mul(U256::from(12345u64)).unwrap();
+        assert_eq!(U256::from_dec(&value.to_string()), Ok(value));
+        assert_eq!(U256::from_hex(&format!("{:x}", value)), Ok(value));
+    }
+}
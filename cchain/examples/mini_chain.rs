@@ -0,0 +1,51 @@
+//! A small chain from genesis to a few transfers: funded accounts, a new
+//! user, payments between them and the balances after every block.
+//!
+//! Run with `cargo run --example mini_chain` from `cchain`.
+
+use blockchain::dev::{dev_account, DevChain};
+use blockchain::{Amount, Transaction, TransactionData};
+
+fn print_balances(dev: &DevChain, accounts: &[String]) {
+    for id in accounts {
+        match dev.chain().get_account_view(id) {
+            Some(view) => println!("  {:<6} {}", id, view.balance()),
+            None => println!("  {:<6} (no account)", id),
+        }
+    }
+}
+
+fn main() {
+    // the genesis block creates and funds two dev accounts
+    let mut dev = DevChain::new(2);
+    let alice = dev_account(0);
+    let bob = dev_account(1);
+    let carol = String::from("carol");
+    let accounts = [alice.clone(), bob.clone(), carol.clone()];
+
+    println!("genesis, {} block(s):", dev.chain().len());
+    print_balances(&dev, &accounts);
+
+    // every submitted transaction is sealed into its own block
+    let create = Transaction::new(alice.clone(), TransactionData::CreateUserAccount(carol.clone()), 1);
+    dev.submit(create).expect("carol doesn't exist yet");
+
+    let payments: [(&String, &String, u128); 3] = [(&alice, &bob, 250), (&bob, &carol, 100), (&alice, &carol, 40)];
+    for (nonce, (from, to, amount)) in payments.iter().enumerate() {
+        let transfer = Transaction::new((*from).clone(), TransactionData::TransferTokens{to: (*to).clone(), amount: Amount::from(*amount)}, nonce as u128 + 2);
+        dev.submit(transfer).expect("senders are funded");
+        println!("{} paid {} {}:", from, to, amount);
+        print_balances(&dev, &accounts);
+    }
+
+    // an invalid transaction is rejected and leaves the chain unchanged
+    let overdraft = Transaction::new(carol.clone(), TransactionData::TransferTokens{to: alice.clone(), amount: Amount::from(1_000_000u128)}, 1);
+    match dev.submit(overdraft) {
+        Ok(()) => println!("overdraft accepted?"),
+        Err(error) => println!("carol's overdraft rejected: {}", error),
+    }
+
+    let last = dev.chain().blocks().last().expect("the chain has a genesis block");
+    let hash: String = last.calculate_hash().iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+    println!("{} blocks, last hash {}...", dev.chain().len(), hash);
+}
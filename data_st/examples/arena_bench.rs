@@ -0,0 +1,119 @@
+//! Times the arena backed structures against their boxed counterparts:
+//! `ArenaList` against `DoublyLinked`, and `ArenaBst` against `Bst`.
+//!
+//! Run with `cargo run --release --example arena_bench` from `data_st`.
+//! Each case runs `ROUNDS` times and the fastest round is reported, so
+//! a busy machine skews the numbers less. Pass a count to change the
+//! elements per round, e.g. `-- 500000`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use linked::{ArenaBst, ArenaList, Bst, DoublyLinked};
+
+const ROUNDS: usize = 5;
+
+const DEFAULT_ELEMENTS: usize = 1_000_000;
+
+
+fn main() {
+    let elements = std::env::args().nth(1)
+        .map(|arg| arg.parse().expect("the element count must be a number"))
+        .unwrap_or(DEFAULT_ELEMENTS);
+    let keys = shuffled_keys(elements);
+    println!("{} elements, fastest of {} rounds", elements, ROUNDS);
+
+    println!("\npush_back then pop_front");
+    compare(
+        || {
+            let mut list = DoublyLinked::new();
+            (0..elements as u64).for_each(|i| { list.push_back(i); });
+            while let Some(i) = list.pop_front() {
+                black_box(i);
+            }
+        },
+        || {
+            let mut list = ArenaList::new();
+            (0..elements as u64).for_each(|i| { list.push_back(i); });
+            while let Some(i) = list.pop_front() {
+                black_box(i);
+            }
+        },
+    );
+
+    println!("\ncollect, sum, then drop");
+    compare(
+        || {
+            black_box((0..elements as u64).collect::<DoublyLinked<u64>>().iter().sum::<u64>());
+        },
+        || {
+            black_box((0..elements as u64).collect::<ArenaList<u64>>().iter().sum::<u64>());
+        },
+    );
+
+    println!("\ntree: insert shuffled keys, look each up, then drop");
+    compare(
+        || {
+            let tree: Bst<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+            black_box(keys.iter().filter_map(|key| tree.get(key)).sum::<u64>());
+        },
+        || {
+            let tree: ArenaBst<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+            black_box(keys.iter().filter_map(|key| tree.get(key)).sum::<u64>());
+        },
+    );
+
+    println!("\ntree: remove every other key, then insert them again");
+    let mut boxed: Bst<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+    let mut arena: ArenaBst<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+    compare(
+        || {
+            for key in keys.iter().step_by(2) {
+                black_box(boxed.remove(key));
+            }
+            for &key in keys.iter().step_by(2) {
+                black_box(boxed.insert(key, key));
+            }
+        },
+        || {
+            for key in keys.iter().step_by(2) {
+                black_box(arena.remove(key));
+            }
+            for &key in keys.iter().step_by(2) {
+                black_box(arena.insert(key, key));
+            }
+        },
+    );
+}
+
+/// Prints the fastest round of each case and the arena's speedup
+fn compare<B: FnMut(), A: FnMut()>(boxed: B, arena: A) {
+    let boxed = fastest(boxed);
+    let arena = fastest(arena);
+    println!("  boxed {:>10.2?}", boxed);
+    println!("  arena {:>10.2?}  ({:.2}x)", arena, boxed.as_secs_f64() / arena.as_secs_f64());
+}
+
+fn fastest<F: FnMut()>(mut case: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let started = Instant::now();
+            case();
+            started.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// `0..count` in a fixed pseudo random order, so the trees stay shallow
+fn shuffled_keys(count: usize) -> Vec<u64> {
+    let mut keys: Vec<u64> = (0..count as u64).collect();
+    let mut state = 0x853c_49e6_748f_ea9b_u64;
+    for i in (1..keys.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        keys.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+    keys
+}
@@ -0,0 +1,57 @@
+//! Walkthrough of `Linked`, the singly linked list: building, reading,
+//! editing in place and sorting.
+//!
+//! Run with `cargo run --example linked_list` from `data_st`.
+
+use linked::Linked;
+
+fn main() {
+    // `add` pushes at the head, `push_back` after the last element
+    let mut list = Linked::new();
+    list.add(2);
+    list.add(1);
+    list.push_back(3);
+    println!("built:       {}", list);
+
+    // lists also come from iterators and vectors
+    let mut numbers: Linked<i32> = vec![5, 3, 8, 1, 9, 2].into_iter().collect();
+    println!("collected:   {}", numbers);
+
+    println!("head:        {:?}", numbers.peek());
+    println!("third:       {:?}", numbers.get(2));
+    println!("has 8:       {}", numbers.contains(&8));
+    println!("length:      {}", numbers.len());
+
+    // indexed edits walk the list, so they are O(n)
+    numbers.insert(1, 7);
+    println!("insert 7:    {}", numbers);
+    let removed = numbers.remove_at(3);
+    println!("remove_at 3: {} (took {:?})", numbers, removed);
+
+    // a cursor edits around one position without walking again
+    let mut cursor = numbers.cursor_front_mut();
+    cursor.move_next();
+    cursor.insert_after(4);
+    if let Some(current) = cursor.current() {
+        *current *= 10;
+    }
+    println!("cursor:      {}", numbers);
+
+    numbers.retain(|n| n % 2 == 1 || *n > 10);
+    println!("retain:      {}", numbers);
+
+    numbers.sort();
+    println!("sorted:      {}", numbers);
+    numbers.reverse();
+    println!("reversed:    {}", numbers);
+
+    // popping hands the elements back from the head
+    while let Some(n) = numbers.pop() {
+        print!("{} ", n);
+    }
+    println!();
+    println!("empty:       {}", numbers.is_empty());
+
+    let doubled: Vec<i32> = list.iter().map(|n| n * 2).collect();
+    println!("doubled:     {:?}", doubled);
+}
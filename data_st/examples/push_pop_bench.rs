@@ -0,0 +1,123 @@
+//! Times pushing and popping at the head of a `Linked` against a list
+//! that clones its chain on every operation, as `add` and `remove` did
+//! before they moved the nodes instead.
+//!
+//! Run with `cargo run --release --example push_pop_bench` from `data_st`.
+//! For each size a list of that many elements is built, then `add` and
+//! `remove` are timed in pairs at its head. Each case runs `ROUNDS` times
+//! and the fastest round is reported, as the time per operation.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use linked::Linked;
+
+const ROUNDS: usize = 5;
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+
+/// Push and pop pairs per round. The cloning list copies the whole chain
+/// on each of them, so it gets far fewer.
+const PAIRS: usize = 10_000;
+
+const CLONING_PAIRS: usize = 10;
+
+
+fn main() {
+    println!("add then remove at the head, fastest of {} rounds", ROUNDS);
+    for &size in SIZES.iter() {
+        println!("\n{} elements", size);
+
+        let mut list: Linked<u64> = (0..size as u64).collect();
+        let moving = fastest(PAIRS, || {
+            list.add(black_box(0));
+            list.remove();
+        });
+
+        let mut cloning = CloningList::with_len(size);
+        let cloned = fastest(CLONING_PAIRS, || {
+            cloning.add(black_box(0));
+            cloning.remove();
+        });
+
+        println!("  cloning {:>12.2?} per pair", cloned);
+        println!("  linked  {:>12.2?} per pair  ({:.0}x)", moving, cloned.as_secs_f64() / moving.as_secs_f64());
+    }
+}
+
+/// Fastest round of `pairs` calls of `pair`, divided by `pairs`
+fn fastest<F: FnMut()>(pairs: usize, mut pair: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let started = Instant::now();
+            for _ in 0..pairs {
+                pair();
+            }
+            started.elapsed() / pairs as u32
+        })
+        .min()
+        .unwrap()
+}
+
+
+/// The list as `add` and `remove` used it: the head is stored inline, so
+/// pushing clones the old head chain behind the new node, and removing
+/// clones the chain after the head into its place
+struct CloningList<T> {
+    head: Option<CloningNode<T>>
+}
+
+struct CloningNode<T> {
+    data: T,
+    next: Option<Box<CloningNode<T>>>
+}
+
+impl<T: Clone> CloningList<T> {
+    fn add(&mut self, data: T) {
+        let next = self.head.clone().map(Box::new);
+        self.head = Some(CloningNode { data, next });
+    }
+
+    fn remove(&mut self) {
+        if let Some(head) = &self.head {
+            self.head = head.next.as_ref().map(|next| next.as_ref().clone());
+        }
+    }
+}
+
+impl CloningList<u64> {
+    /// `len` elements, built without cloning so only the timed calls do
+    fn with_len(len: usize) -> Self {
+        let mut head = None;
+        for data in 0..len as u64 {
+            head = Some(CloningNode { data, next: head.map(Box::new) });
+        }
+        CloningList { head }
+    }
+}
+
+/// Copies every node like the derived `Clone` did, but in a loop, so long
+/// chains don't overflow the stack
+impl<T: Clone> Clone for CloningNode<T> {
+    fn clone(&self) -> Self {
+        let mut copy = CloningNode { data: self.data.clone(), next: None };
+        let mut source = self.next.as_deref();
+        let mut slot = &mut copy.next;
+        while let Some(node) = source {
+            let node_copy = slot.insert(Box::new(CloningNode { data: node.data.clone(), next: None }));
+            slot = &mut node_copy.next;
+            source = node.next.as_deref();
+        }
+        copy
+    }
+}
+
+/// Frees the chain in a loop, for the same reason
+impl<T> Drop for CloningNode<T> {
+    fn drop(&mut self) {
+        let mut next = self.next.take();
+        while let Some(mut node) = next {
+            next = node.next.take();
+        }
+    }
+}
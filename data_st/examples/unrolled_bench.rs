@@ -0,0 +1,120 @@
+//! Times `Unrolled` against `Linked` and `Vec`, iterating and inserting.
+//!
+//! Run with `cargo run --release --example unrolled_bench` from `data_st`.
+//! Each case runs `ROUNDS` times and the fastest round is reported, so
+//! a busy machine skews the numbers less. Pass a count to change the
+//! elements per round, e.g. `-- 500000`. Inserting into the middle walks
+//! the lists, so that case uses a hundredth of the elements.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use linked::{Linked, Unrolled};
+
+const ROUNDS: usize = 5;
+
+const DEFAULT_ELEMENTS: usize = 1_000_000;
+
+
+fn main() {
+    let elements = std::env::args().nth(1)
+        .map(|arg| arg.parse().expect("the element count must be a number"))
+        .unwrap_or(DEFAULT_ELEMENTS);
+    println!("{} elements, fastest of {} rounds", elements, ROUNDS);
+
+    println!("\npush_back then drop");
+    compare(
+        || {
+            let mut list = Linked::new();
+            (0..elements as u64).for_each(|i| list.push_back(i));
+            black_box(list.len());
+        },
+        || {
+            let mut list = Unrolled::new();
+            (0..elements as u64).for_each(|i| list.push_back(i));
+            black_box(list.len());
+        },
+        || {
+            let mut vec = Vec::new();
+            (0..elements as u64).for_each(|i| vec.push(i));
+            black_box(vec.len());
+        },
+    );
+
+    println!("\niterate and sum");
+    let linked: Linked<u64> = (0..elements as u64).collect();
+    let unrolled: Unrolled<u64> = (0..elements as u64).collect();
+    let vec: Vec<u64> = (0..elements as u64).collect();
+    compare(
+        || {
+            black_box(linked.iter().sum::<u64>());
+        },
+        || {
+            black_box(unrolled.iter().sum::<u64>());
+        },
+        || {
+            black_box(vec.iter().sum::<u64>());
+        },
+    );
+
+    let positions = insert_positions(elements / 100);
+    println!("\ninsert {} elements at pseudo random positions", positions.len());
+    compare(
+        || {
+            let mut list = Linked::new();
+            for (i, &index) in positions.iter().enumerate() {
+                list.insert(index, i);
+            }
+            black_box(list.len());
+        },
+        || {
+            let mut list = Unrolled::new();
+            for (i, &index) in positions.iter().enumerate() {
+                list.insert(index, i);
+            }
+            black_box(list.len());
+        },
+        || {
+            let mut vec = Vec::new();
+            for (i, &index) in positions.iter().enumerate() {
+                vec.insert(index, i);
+            }
+            black_box(vec.len());
+        },
+    );
+}
+
+/// Prints the fastest round of each case and how `Unrolled` compares
+fn compare<L: FnMut(), U: FnMut(), V: FnMut()>(linked: L, unrolled: U, vec: V) {
+    let linked = fastest(linked);
+    let unrolled = fastest(unrolled);
+    let vec = fastest(vec);
+    println!("  linked   {:>10.2?}", linked);
+    println!("  unrolled {:>10.2?}  ({:.2}x linked)", unrolled, linked.as_secs_f64() / unrolled.as_secs_f64());
+    println!("  vec      {:>10.2?}  ({:.2}x unrolled)", vec, unrolled.as_secs_f64() / vec.as_secs_f64());
+}
+
+fn fastest<F: FnMut()>(mut case: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let started = Instant::now();
+            case();
+            started.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// For each of `count` insertions, a fixed pseudo random index no greater
+/// than the length at that point
+fn insert_positions(count: usize) -> Vec<usize> {
+    let mut state = 0x853c_49e6_748f_ea9b_u64;
+    (0..count)
+        .map(|len| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (len as u64 + 1)) as usize
+        })
+        .collect()
+}
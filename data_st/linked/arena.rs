@@ -0,0 +1,365 @@
+//! Slab of values addressed by generational indexes
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::NonZeroU32;
+
+/// Refers to a value in an `Arena`. Once the value is removed its slot
+/// may be reused, but the slot's generation moves on, so the old index
+/// doesn't refer to the new value. Generations are 32 bits and wrap, so
+/// only an index kept across 2^32 reuses of its slot could alias.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct Index {
+    slot: u32,
+
+    /// Never 0, so an `Option<Index>` is no bigger than an `Index`
+    generation: NonZeroU32
+}
+
+enum Entry<T> {
+    Occupied {
+        generation: NonZeroU32,
+        value: T
+    },
+    Free {
+        /// Generation the next value in this slot gets
+        generation: NonZeroU32,
+        next_free: Option<u32>
+    },
+}
+
+
+/// Values stored contiguously, with O(1) insert and remove. Slots are
+/// never moved, so indexes stay valid until their value is removed.
+pub struct Arena<T> {
+    entries: Vec<Entry<T>>,
+
+    /// Head of the list of free slots, threaded through the entries
+    free_head: Option<u32>,
+
+    len: usize
+}
+
+pub struct Iter<'a, T> {
+    entries: std::iter::Enumerate<std::slice::Iter<'a, Entry<T>>>,
+    remaining: usize
+}
+
+pub struct IterMut<'a, T> {
+    entries: std::iter::Enumerate<std::slice::IterMut<'a, Entry<T>>>,
+    remaining: usize
+}
+
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena::with_capacity(0)
+    }
+
+    /// Arena with room for `capacity` values before it reallocates
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            entries: Vec::with_capacity(capacity),
+            free_head: None,
+            len: 0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Stores `value`, reusing the most recently freed slot if any.
+    /// Panics if the arena already holds `u32::MAX` values.
+    pub fn insert(&mut self, value: T) -> Index {
+        match self.free_head {
+            Some(slot) => {
+                let generation = match self.entries[slot as usize] {
+                    Entry::Free { generation, next_free } => {
+                        self.free_head = next_free;
+                        generation
+                    }
+                    Entry::Occupied { .. } => unreachable!("free list points to an occupied slot"),
+                };
+                self.entries[slot as usize] = Entry::Occupied {
+                    generation,
+                    value
+                };
+                self.len += 1;
+                Index {
+                    slot,
+                    generation
+                }
+            }
+            None => {
+                let slot = match u32::try_from(self.entries.len()) {
+                    Ok(slot) if slot < u32::MAX => slot,
+                    _ => panic!("arena can't hold more than {} values", u32::MAX),
+                };
+                self.entries.push(Entry::Occupied {
+                    generation: NonZeroU32::MIN,
+                    value
+                });
+                self.len += 1;
+                Index {
+                    slot,
+                    generation: NonZeroU32::MIN
+                }
+            }
+        }
+    }
+
+    /// Takes out the value at `index`, `None` if it was already removed
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        if !self.contains(index) {
+            return None;
+        }
+        let free = Entry::Free {
+            generation: next_generation(index.generation),
+            next_free: self.free_head
+        };
+        self.free_head = Some(index.slot);
+        self.len -= 1;
+        match std::mem::replace(&mut self.entries[index.slot as usize], free) {
+            Entry::Occupied { value, .. } => Some(value),
+            Entry::Free { .. } => unreachable!(),
+        }
+    }
+
+    pub fn contains(&self, index: Index) -> bool {
+        self.get(index).is_some()
+    }
+
+    pub fn get(&self, index: Index) -> Option<&T> {
+        match self.entries.get(index.slot as usize)? {
+            Entry::Occupied { generation, value } if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        match self.entries.get_mut(index.slot as usize)? {
+            Entry::Occupied { generation, value } if *generation == index.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Removes every value, keeping the slots. Indexes handed out before
+    /// no longer refer to anything.
+    pub fn clear(&mut self) {
+        self.free_head = None;
+        for (slot, entry) in self.entries.iter_mut().enumerate().rev() {
+            let generation = match entry {
+                Entry::Occupied { generation, .. } => next_generation(*generation),
+                Entry::Free { generation, .. } => *generation,
+            };
+            *entry = Entry::Free {
+                generation,
+                next_free: self.free_head
+            };
+            self.free_head = Some(slot as u32);
+        }
+        self.len = 0;
+    }
+
+    /// Values with their indexes, in slot order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            entries: self.entries.iter().enumerate(),
+            remaining: self.len
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            remaining: self.len,
+            entries: self.entries.iter_mut().enumerate()
+        }
+    }
+}
+
+
+fn next_generation(generation: NonZeroU32) -> NonZeroU32 {
+    generation.checked_add(1).unwrap_or(NonZeroU32::MIN)
+}
+
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Index, &'a T);
+
+    fn next(&mut self) -> Option<(Index, &'a T)> {
+        for (slot, entry) in self.entries.by_ref() {
+            if let Entry::Occupied { generation, value } = entry {
+                self.remaining -= 1;
+                return Some((Index { slot: slot as u32, generation: *generation }, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (Index, &'a mut T);
+
+    fn next(&mut self) -> Option<(Index, &'a mut T)> {
+        for (slot, entry) in self.entries.by_ref() {
+            if let Entry::Occupied { generation, value } = entry {
+                self.remaining -= 1;
+                return Some((Index { slot: slot as u32, generation: *generation }, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Arena<T> {
+    type Item = (Index, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Arena<T> {
+    type Item = (Index, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_stale_index_misses_the_value_reusing_its_slot() {
+        let mut arena = Arena::new();
+        let first = arena.insert("first");
+        let kept = arena.insert("kept");
+        assert_eq!(arena.remove(first), Some("first"));
+
+        let second = arena.insert("second");
+        assert_eq!(second.slot, first.slot);
+        assert_ne!(second, first);
+
+        assert_eq!(arena.get(first), None);
+        assert_eq!(arena.get_mut(first), None);
+        assert!(!arena.contains(first));
+        assert_eq!(arena.remove(first), None);
+        assert_eq!(arena.get(second), Some(&"second"));
+        assert_eq!(arena.get(kept), Some(&"kept"));
+        assert_eq!(arena.len(), 2);
+    }
+
+    #[test]
+    fn clearing_invalidates_every_index() {
+        let mut arena = Arena::new();
+        let old: Vec<Index> = (0..10).map(|i| arena.insert(i)).collect();
+        arena.remove(old[3]);
+        arena.clear();
+        assert!(arena.is_empty());
+
+        let new: Vec<Index> = (0..10).map(|i| arena.insert(i * 100)).collect();
+        assert!(old.iter().all(|&index| arena.get(index).is_none()));
+        assert!(new.iter().all(|&index| arena.contains(index)));
+        // the slots are reused from the first one up
+        assert_eq!(new.iter().map(|index| index.slot).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn an_index_past_the_last_slot_misses() {
+        let arena: Arena<u32> = Arena::new();
+        let mut other = Arena::new();
+        let index = other.insert(1);
+        assert_eq!(arena.get(index), None);
+    }
+
+    #[test]
+    fn generations_wrap_past_zero() {
+        let max = NonZeroU32::new(u32::MAX).unwrap();
+        assert_eq!(next_generation(max), NonZeroU32::MIN);
+
+        let mut arena = Arena::new();
+        let old = arena.insert(0);
+        arena.entries[0] = Entry::Free { generation: max, next_free: None };
+        arena.free_head = Some(0);
+        arena.len = 0;
+        let last = arena.insert(1);
+        assert_eq!(last.generation, max);
+        arena.remove(last);
+        let wrapped = arena.insert(2);
+        assert_eq!(wrapped.generation, NonZeroU32::MIN);
+        // after 2^32 reuses of a slot, a stale index aliases again
+        assert_eq!(arena.get(old), Some(&2));
+        assert_eq!(arena.get(last), None);
+    }
+
+    #[test]
+    fn random_use_matches_a_vec_of_live_and_dead_indexes() {
+        let mut state = 0x9e37_79b9_u64;
+        let mut arena = Arena::new();
+        let mut live: Vec<(Index, u64)> = Vec::new();
+        let mut dead: Vec<Index> = Vec::new();
+        for step in 0..10_000u64 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            match state % 3 {
+                0 if !live.is_empty() => {
+                    let (index, value) = live.swap_remove(state as usize / 3 % live.len());
+                    assert_eq!(arena.remove(index), Some(value));
+                    dead.push(index);
+                }
+                _ => live.push((arena.insert(step), step)),
+            }
+        }
+        assert_eq!(arena.len(), live.len());
+        assert!(live.iter().all(|(index, value)| arena.get(*index) == Some(value)));
+        assert!(dead.iter().all(|&index| arena.get(index).is_none()));
+
+        let iter = arena.iter();
+        assert_eq!(iter.size_hint(), (live.len(), Some(live.len())));
+        let mut seen: Vec<u64> = iter.map(|(_, &value)| value).collect();
+        let mut expected: Vec<u64> = live.iter().map(|&(_, value)| value).collect();
+        seen.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn an_optional_index_is_no_bigger() {
+        assert_eq!(std::mem::size_of::<Option<Index>>(), std::mem::size_of::<Index>());
+    }
+}
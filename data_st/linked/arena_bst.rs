@@ -0,0 +1,337 @@
+//! Unbalanced binary search tree map whose nodes live in an `Arena`
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::arena::{Arena, Index};
+
+struct ArenaBstNode<K, V> {
+    key: K,
+    value: V,
+    left: Option<Index>,
+    right: Option<Index>
+}
+
+/// Where a child index is kept: the root or a side of a node
+#[derive(Clone,Copy)]
+enum Link {
+    Root,
+    Left(Index),
+    Right(Index),
+}
+
+
+/// `Bst` with its nodes in one arena instead of a box each. It has the
+/// same shape, unbalanced, as a `Bst` given the same operations, but
+/// dropping it frees a single allocation with no walk over the nodes.
+pub struct ArenaBst<K, V> {
+    nodes: Arena<ArenaBstNode<K, V>>,
+    root: Option<Index>
+}
+
+/// In order iterator, from the smallest key to the largest
+pub struct Iter<'a, K, V> {
+    tree: &'a ArenaBst<K, V>,
+
+    /// Nodes whose left subtree was visited, the next one on top
+    stack: Vec<&'a ArenaBstNode<K, V>>,
+    remaining: usize
+}
+
+
+impl<K: Ord, V> ArenaBst<K, V> {
+    pub fn new() -> Self {
+        ArenaBst::with_capacity(0)
+    }
+
+    /// Tree with room for `capacity` entries before it reallocates
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaBst {
+            nodes: Arena::with_capacity(capacity),
+            root: None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let link = self.find(&key);
+        if let Some(index) = self.child(link) {
+            return Some(std::mem::replace(&mut self.node_mut(index).value, value));
+        }
+        let index = self.nodes.insert(ArenaBstNode {
+            key,
+            value,
+            left: None,
+            right: None
+        });
+        self.set_child(link, Some(index));
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.child(self.find(key))?;
+        Some(&self.node(index).value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.child(self.find(key))?;
+        Some(&mut self.node_mut(index).value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes `key` and returns its value. A node with two children is
+    /// replaced by the smallest node of its right subtree, as in `Bst`.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let link = self.find(key);
+        let node = self.nodes.remove(self.child(link)?)?;
+
+        let replacement = match (node.left, node.right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(left), Some(right)) => {
+                let mut parent = None;
+                let mut min = right;
+                while let Some(next) = self.node(min).left {
+                    parent = Some(min);
+                    min = next;
+                }
+                if let Some(parent) = parent {
+                    self.node_mut(parent).left = self.node(min).right;
+                    self.node_mut(min).right = Some(right);
+                }
+                self.node_mut(min).left = Some(left);
+                Some(min)
+            }
+        };
+        self.set_child(link, replacement);
+        Some(node.value)
+    }
+
+    /// Entry with the smallest key
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut node = self.node(self.root?);
+        while let Some(left) = node.left {
+            node = self.node(left);
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Entry with the largest key
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut node = self.node(self.root?);
+        while let Some(right) = node.right {
+            node = self.node(right);
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Number of nodes on the longest path from the root, 0 when empty
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level: Vec<Index> = self.root.into_iter().collect();
+        while !level.is_empty() {
+            height += 1;
+            level = level.iter()
+                .flat_map(|&index| self.node(index).left.into_iter().chain(self.node(index).right))
+                .collect();
+        }
+        height
+    }
+
+    /// Removes every entry, keeping the arena's slots for reuse
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = None;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            tree: self,
+            stack: Vec::new(),
+            remaining: self.len()
+        };
+        iter.push_left(self.root);
+        iter
+    }
+
+    /// Link holding `key`, or the empty link it would be inserted at
+    fn find(&self, key: &K) -> Link {
+        let mut link = Link::Root;
+        while let Some(index) = self.child(link) {
+            link = match key.cmp(&self.node(index).key) {
+                Ordering::Less => Link::Left(index),
+                Ordering::Greater => Link::Right(index),
+                Ordering::Equal => return link,
+            };
+        }
+        link
+    }
+}
+
+impl<K, V> ArenaBst<K, V> {
+    fn child(&self, link: Link) -> Option<Index> {
+        match link {
+            Link::Root => self.root,
+            Link::Left(index) => self.node(index).left,
+            Link::Right(index) => self.node(index).right,
+        }
+    }
+
+    fn set_child(&mut self, link: Link, child: Option<Index>) {
+        match link {
+            Link::Root => self.root = child,
+            Link::Left(index) => self.node_mut(index).left = child,
+            Link::Right(index) => self.node_mut(index).right = child,
+        }
+    }
+
+    fn node(&self, index: Index) -> &ArenaBstNode<K, V> {
+        self.nodes.get(index).expect("tree links to a removed node")
+    }
+
+    fn node_mut(&mut self, index: Index) -> &mut ArenaBstNode<K, V> {
+        self.nodes.get_mut(index).expect("tree links to a removed node")
+    }
+}
+
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut next: Option<Index>) {
+        while let Some(index) = next {
+            let node = self.tree.node(index);
+            self.stack.push(node);
+            next = node.left;
+        }
+    }
+}
+
+
+impl<K: Ord, V> Default for ArenaBst<K, V> {
+    fn default() -> Self {
+        ArenaBst::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for ArenaBst<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for ArenaBst<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = ArenaBst::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for ArenaBst<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right);
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a ArenaBst<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{check_map, random_map_ops, MapOp};
+    use crate::Bst;
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn random_operations_match_a_btree_map() {
+        for seed in 1..=20 {
+            for &keys in [4, 64, 1000].iter() {
+                check_map(&mut ArenaBst::new(), &random_map_ops(seed, 1000, keys)).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn removing_inner_nodes_matches_a_btree_map() {
+        let mut ops: Vec<MapOp<u32, u32>> = [8, 4, 12, 2, 6, 10, 14, 1, 3, 5, 7, 9, 11, 13, 15]
+            .iter()
+            .map(|&key| MapOp::Insert(key, key * 10))
+            .collect();
+        ops.extend([8, 4, 12, 6, 1, 8, 15].iter().map(|&key| MapOp::Remove(key)));
+        ops.extend((0..17).map(MapOp::Get));
+        check_map(&mut ArenaBst::new(), &ops).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn the_same_operations_build_the_same_shape_as_a_bst() {
+        let mut tree = ArenaBst::new();
+        let mut boxed = Bst::new();
+        for op in random_map_ops(5, 3000, 500) {
+            match op {
+                MapOp::Insert(key, value) => assert_eq!(tree.insert(key, value), boxed.insert(key, value)),
+                MapOp::Remove(key) => assert_eq!(tree.remove(&key), boxed.remove(&key)),
+                _ => {}
+            }
+            assert_eq!(tree.height(), boxed.height());
+        }
+        assert!(tree.iter().eq(boxed.iter()));
+        assert_eq!(tree.min(), boxed.min());
+        assert_eq!(tree.max(), boxed.max());
+    }
+
+    #[test]
+    fn cleared_slots_are_reused() {
+        let mut tree: ArenaBst<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        let capacity = tree.nodes.capacity();
+        tree.clear();
+        assert!(tree.is_empty());
+        tree.extend((0..100).rev().map(|key| (key, key)));
+        assert_eq!(tree.nodes.capacity(), capacity);
+        assert_eq!(tree.len(), 100);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn a_degenerate_tree_drops_without_walking_it() {
+        let tree: ArenaBst<u32, ()> = (0..5_000).map(|key| (key, ())).collect();
+        assert_eq!(tree.height(), 5_000);
+        assert_eq!(tree.len(), 5_000);
+    }
+}
@@ -0,0 +1,231 @@
+//! Doubly linked list whose nodes live in an `Arena`
+
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::arena::{Arena, Index};
+
+struct ArenaNode<T> {
+    data: T,
+    prev: Option<Index>,
+    next: Option<Index>
+}
+
+
+/// Doubly linked list without a heap allocation per node or any unsafe
+/// code. `push_front` and `push_back` return the element's index, which
+/// `remove` checks, so a stale index is simply `None`, unlike a
+/// `DoublyLinked` node handle.
+pub struct ArenaList<T> {
+    nodes: Arena<ArenaNode<T>>,
+    head: Option<Index>,
+    tail: Option<Index>
+}
+
+pub struct Iter<'a, T> {
+    list: &'a ArenaList<T>,
+    head: Option<Index>,
+    tail: Option<Index>,
+    len: usize
+}
+
+
+impl<T> ArenaList<T> {
+    pub fn new() -> Self {
+        ArenaList::with_capacity(0)
+    }
+
+    /// List with room for `capacity` elements before it reallocates
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaList {
+            nodes: Arena::with_capacity(capacity),
+            head: None,
+            tail: None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Pushes `data` at the head in O(1)
+    pub fn push_front(&mut self, data: T) -> Index {
+        let index = self.nodes.insert(ArenaNode {
+            data,
+            prev: None,
+            next: self.head
+        });
+        match self.head {
+            Some(head) => self.node_mut(head).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        self.head = Some(index);
+        index
+    }
+
+    /// Pushes `data` after the last element in O(1)
+    pub fn push_back(&mut self, data: T) -> Index {
+        let index = self.nodes.insert(ArenaNode {
+            data,
+            prev: self.tail,
+            next: None
+        });
+        match self.tail {
+            Some(tail) => self.node_mut(tail).next = Some(index),
+            None => self.head = Some(index),
+        }
+        self.tail = Some(index);
+        index
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.head?)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.tail?)
+    }
+
+    /// Removes the element at `index` in O(1), `None` if it was
+    /// already removed
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        let node = self.nodes.remove(index)?;
+        match node.prev {
+            Some(prev) => self.node_mut(prev).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.node_mut(next).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        Some(node.data)
+    }
+
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.nodes.get(index).map(|node| &node.data)
+    }
+
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        self.nodes.get_mut(index).map(|node| &mut node.data)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(self.head?)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.tail?)
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    /// Iterates from the head; `rev()` iterates from the last element
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            head: self.head,
+            tail: self.tail,
+            len: self.len()
+        }
+    }
+
+    fn node(&self, index: Index) -> &ArenaNode<T> {
+        self.nodes.get(index).expect("list links to a removed node")
+    }
+
+    fn node_mut(&mut self, index: Index) -> &mut ArenaNode<T> {
+        self.nodes.get_mut(index).expect("list links to a removed node")
+    }
+}
+
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        ArenaList::new()
+    }
+}
+
+impl<T: Clone> Clone for ArenaList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = ArenaList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.head?);
+        self.head = node.next;
+        self.len -= 1;
+        Some(&node.data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.tail?);
+        self.tail = node.prev;
+        self.len -= 1;
+        Some(&node.data)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,318 @@
+//! Bloom filter: a compact set that may report false positives but never
+//! false negatives
+
+use std::convert::TryInto;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Bytes before the bit words in `to_bytes`: bit count, hash count and
+/// inserted items
+const HEADER_LENGTH: usize = 8 + 4 + 8;
+
+
+/// Set of `T` answering membership with a bounded false positive rate.
+/// Hashing is deterministic, so a filter serialized with `to_bytes` gives
+/// the same answers when read back on a machine of the same endianness.
+pub struct BloomFilter<T: ?Sized> {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+
+    /// Insert calls, used to estimate the current false positive rate
+    items: u64,
+
+    marker: PhantomData<fn(&T)>
+}
+
+/// 64 bit FNV-1a, stable across runs unlike std's `RandomState`
+struct Fnv64(u64);
+
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    /// Filter sized to hold `expected_items` with the given false positive
+    /// rate, which must be strictly between 0 and 1
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Result<Self, &'static str> {
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err("False positive rate must be between 0 and 1");
+        }
+
+        // m = -n ln p / (ln 2)^2 and k = m / n ln 2
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().max(1.0) as u32;
+        Ok(BloomFilter::with_size(num_bits, num_hashes))
+    }
+
+    /// Filter of `num_bits` bits, rounded up to whole words, setting
+    /// `num_hashes` bits per item
+    pub fn with_size(num_bits: u64, num_hashes: u32) -> Self {
+        let words = num_bits.max(1).div_ceil(64);
+        BloomFilter {
+            bits: vec![0; words as usize],
+            num_bits: words * 64,
+            num_hashes: num_hashes.max(1),
+            items: 0,
+            marker: PhantomData
+        }
+    }
+
+    pub fn num_bits(&self) -> u64 {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        for bit in self.bit_indexes(item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.items += 1;
+    }
+
+    /// False means `item` was never inserted; true means it probably was
+    pub fn maybe_contains(&self, item: &T) -> bool {
+        self.bit_indexes(item).all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Expected false positive rate given the items inserted so far
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let k = self.num_hashes as f64;
+        let exponent = -k * self.items as f64 / self.num_bits as f64;
+        (1.0 - exponent.exp()).powf(k)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        self.items = 0;
+    }
+
+    /// Adds everything in `other`, which must have the same size and
+    /// hash count
+    pub fn union(&mut self, other: &BloomFilter<T>) -> Result<(), &'static str> {
+        self.check_compatible(other)?;
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other;
+        }
+        self.items += other.items;
+        Ok(())
+    }
+
+    /// Keeps only bits set in both filters. The result may answer true
+    /// for items in just one of them, like any Bloom filter.
+    pub fn intersection(&mut self, other: &BloomFilter<T>) -> Result<(), &'static str> {
+        self.check_compatible(other)?;
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word &= other;
+        }
+        self.items = self.items.min(other.items);
+        Ok(())
+    }
+
+    /// Header of bit count, hash count and items, then the bit words,
+    /// all little endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + self.bits.len() * 8);
+        bytes.extend_from_slice(&self.num_bits.to_le_bytes());
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        bytes.extend_from_slice(&self.items.to_le_bytes());
+        for word in self.bits.iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() < HEADER_LENGTH {
+            return Err("Bloom filter data is too short");
+        }
+        let num_bits = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let num_hashes = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        let items = u64::from_le_bytes(bytes[12..20].try_into().unwrap());
+
+        let words = &bytes[HEADER_LENGTH..];
+        if num_bits == 0 || num_bits % 64 != 0 || num_hashes == 0 || words.len() as u64 != num_bits / 8 {
+            return Err("Bloom filter data is malformed");
+        }
+
+        Ok(BloomFilter {
+            bits: words.chunks(8).map(|word| u64::from_le_bytes(word.try_into().unwrap())).collect(),
+            num_bits,
+            num_hashes,
+            items,
+            marker: PhantomData
+        })
+    }
+
+    fn check_compatible(&self, other: &BloomFilter<T>) -> Result<(), &'static str> {
+        if self.num_bits != other.num_bits || self.num_hashes != other.num_hashes {
+            return Err("Bloom filters have different sizes");
+        }
+        Ok(())
+    }
+
+    /// Bits of `item`, by double hashing: the i-th is h1 + i * h2
+    fn bit_indexes(&self, item: &T) -> impl Iterator<Item = u64> {
+        let mut hasher = Fnv64(0xcbf29ce484222325);
+        item.hash(&mut hasher);
+        let h1 = hasher.finish();
+        let h2 = mix(h1) | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+
+// Written out so the item type needs no bounds of its own
+impl<T: ?Sized> Clone for BloomFilter<T> {
+    fn clone(&self) -> Self {
+        BloomFilter {
+            bits: self.bits.clone(),
+            num_bits: self.num_bits,
+            num_hashes: self.num_hashes,
+            items: self.items,
+            marker: PhantomData
+        }
+    }
+}
+
+impl<T: ?Sized> PartialEq for BloomFilter<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_hashes == other.num_hashes && self.items == other.items && self.bits == other.bits
+    }
+}
+
+impl<T: ?Sized> Eq for BloomFilter<T> {}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+
+/// SplitMix64 finalizer, to derive a second hash from the first
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+
+impl Hasher for Fnv64 {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn inserted_items_are_always_found() {
+        for &rate in [0.5, 0.1, 0.01, 0.0001].iter() {
+            let mut filter = BloomFilter::new(5_000, rate).unwrap();
+            for i in 0..5_000u64 {
+                filter.insert(&i);
+            }
+            // far past the expected items, so most bits are set
+            for i in 5_000..20_000u64 {
+                filter.insert(&(i * 7919));
+            }
+            assert!((0..5_000u64).all(|i| filter.maybe_contains(&i)));
+            assert!((5_000..20_000u64).all(|i| filter.maybe_contains(&(i * 7919))));
+        }
+    }
+
+    #[test]
+    fn unsized_items_are_always_found() {
+        let words: Vec<String> = (0..1_000).map(|i| format!("word{}", i)).collect();
+        let mut filter = BloomFilter::<str>::new(words.len(), 0.01).unwrap();
+        for word in words.iter() {
+            filter.insert(word);
+        }
+        assert!(words.iter().all(|word| filter.maybe_contains(word)));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn false_positives_stay_near_the_requested_rate() {
+        let mut filter = BloomFilter::new(10_000, 0.01).unwrap();
+        for i in 0..10_000u64 {
+            filter.insert(&i);
+        }
+        let false_positives = (10_000..110_000u64).filter(|i| filter.maybe_contains(i)).count();
+        assert!(false_positives < 2_000, "{} false positives in 100000", false_positives);
+        assert!((filter.estimated_false_positive_rate() - 0.01).abs() < 0.005);
+    }
+
+    #[test]
+    fn to_bytes_round_trips() {
+        let mut filter = BloomFilter::new(500, 0.05).unwrap();
+        for i in (0..1_000u32).step_by(2) {
+            filter.insert(&i);
+        }
+        let bytes = filter.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LENGTH + filter.num_bits() as usize / 8);
+
+        let read: BloomFilter<u32> = BloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(read, filter);
+        assert_eq!(read.num_bits(), filter.num_bits());
+        assert_eq!(read.num_hashes(), filter.num_hashes());
+        assert!((0..1_000u32).all(|i| read.maybe_contains(&i) == filter.maybe_contains(&i)));
+        assert_eq!(read.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_data() {
+        let bytes = BloomFilter::<u32>::with_size(128, 3).to_bytes();
+        assert_eq!(BloomFilter::<u32>::from_bytes(&bytes[..HEADER_LENGTH - 1]), Err("Bloom filter data is too short"));
+        assert_eq!(BloomFilter::<u32>::from_bytes(&bytes[..bytes.len() - 8]), Err("Bloom filter data is malformed"));
+
+        let mut no_hashes = bytes.clone();
+        no_hashes[8..12].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(BloomFilter::<u32>::from_bytes(&no_hashes), Err("Bloom filter data is malformed"));
+
+        let mut odd_bits = bytes;
+        odd_bits[0..8].copy_from_slice(&100u64.to_le_bytes());
+        assert_eq!(BloomFilter::<u32>::from_bytes(&odd_bits), Err("Bloom filter data is malformed"));
+    }
+
+    #[test]
+    fn a_union_finds_the_items_of_both_filters() {
+        let mut evens = BloomFilter::with_size(4096, 4);
+        let mut odds = BloomFilter::with_size(4096, 4);
+        for i in 0..300u32 {
+            if i % 2 == 0 {
+                evens.insert(&i);
+            } else {
+                odds.insert(&i);
+            }
+        }
+        let mut both = evens.clone();
+        both.union(&odds).unwrap();
+        assert!((0..300u32).all(|i| both.maybe_contains(&i)));
+
+        evens.intersection(&both).unwrap();
+        assert!((0..300u32).step_by(2).all(|i| evens.maybe_contains(&i)));
+        assert!(evens.union(&BloomFilter::with_size(8192, 4)).is_err());
+        assert!(evens.union(&BloomFilter::with_size(4096, 5)).is_err());
+    }
+}
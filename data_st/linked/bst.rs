@@ -0,0 +1,326 @@
+//! Unbalanced binary search tree map
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+
+use crate::memory::MemoryFootprint;
+
+type Tree<K, V> = Option<Box<BstNode<K, V>>>;
+
+struct BstNode<K, V> {
+    key: K,
+    value: V,
+    left: Tree<K, V>,
+    right: Tree<K, V>
+}
+
+
+/// Map kept as a binary search tree. It is not rebalanced, so sorted
+/// insertions degrade it to a list; every operation walks the tree
+/// iteratively so that never overflows the stack.
+pub struct Bst<K, V> {
+    root: Tree<K, V>,
+    len: usize
+}
+
+/// In order iterator, from the smallest key to the largest
+pub struct Iter<'a, K, V> {
+    /// Nodes whose left subtree was visited, the next one on top
+    stack: Vec<&'a BstNode<K, V>>,
+    remaining: usize
+}
+
+
+impl<K: Ord, V> Bst<K, V> {
+    pub fn new() -> Self {
+        Bst {
+            root: None,
+            len: 0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let slot = self.slot_mut(&key);
+        match slot {
+            Some(node) => Some(std::mem::replace(&mut node.value, value)),
+            None => {
+                *slot = Some(Box::new(BstNode {
+                    key,
+                    value,
+                    left: None,
+                    right: None
+                }));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(&current.key) {
+                Ordering::Less => current.left.as_deref(),
+                Ordering::Greater => current.right.as_deref(),
+                Ordering::Equal => return Some(&current.value),
+            };
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.slot_mut(key).as_mut().map(|node| &mut node.value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes `key` and returns its value. A node with two children is
+    /// replaced by the smallest node of its right subtree.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.slot_mut(key);
+        let mut node = slot.take()?;
+
+        *slot = match (node.left.take(), node.right.take()) {
+            (None, right) => right,
+            (left, None) => left,
+            (left, Some(right)) => {
+                let mut right = Some(right);
+                let mut min_slot = &mut right;
+                while min_slot.as_ref().unwrap().left.is_some() {
+                    min_slot = &mut min_slot.as_mut().unwrap().left;
+                }
+                let mut min = min_slot.take().unwrap();
+                *min_slot = min.right.take();
+                min.left = left;
+                min.right = right;
+                Some(min)
+            }
+        };
+
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Entry with the smallest key
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Entry with the largest key
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Number of nodes on the longest path from the root, 0 when empty
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level: Vec<&BstNode<K, V>> = self.root.as_deref().into_iter().collect();
+        while !level.is_empty() {
+            height += 1;
+            level = level.iter()
+                .flat_map(|node| node.left.as_deref().into_iter().chain(node.right.as_deref()))
+                .collect();
+        }
+        height
+    }
+
+    pub fn clear(&mut self) {
+        drop_tree(self.root.take());
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Slot holding `key`, or the empty slot it would be inserted into
+    fn slot_mut(&mut self, key: &K) -> &mut Tree<K, V> {
+        let mut slot = &mut self.root;
+        loop {
+            let ordering = match slot.as_ref() {
+                Some(node) => key.cmp(&node.key),
+                None => return slot,
+            };
+            slot = match ordering {
+                Ordering::Less => &mut slot.as_mut().unwrap().left,
+                Ordering::Greater => &mut slot.as_mut().unwrap().right,
+                Ordering::Equal => return slot,
+            };
+        }
+    }
+}
+
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a BstNode<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+
+/// Frees the nodes from an explicit stack rather than recursing down
+/// a possibly list shaped tree
+fn drop_tree<K, V>(root: Tree<K, V>) {
+    let mut pending: Vec<Box<BstNode<K, V>>> = root.into_iter().collect();
+    while let Some(mut node) = pending.pop() {
+        pending.extend(node.left.take());
+        pending.extend(node.right.take());
+    }
+}
+
+
+impl<K, V> Drop for Bst<K, V> {
+    fn drop(&mut self) {
+        drop_tree(self.root.take());
+    }
+}
+
+/// Walks the nodes with a stack, so degenerate trees don't overflow it
+impl<K: MemoryFootprint, V: MemoryFootprint> MemoryFootprint for Bst<K, V> {
+    fn heap_size(&self) -> usize {
+        let mut size = 0;
+        let mut stack: Vec<&BstNode<K, V>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            size += mem::size_of::<BstNode<K, V>>() + node.key.heap_size() + node.value.heap_size();
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        size
+    }
+}
+
+impl<K: Ord, V> Default for Bst<K, V> {
+    fn default() -> Self {
+        Bst::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for Bst<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for Bst<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Bst::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for Bst<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a Bst<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::testing::{check_map, random_map_ops, MapOp};
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn random_operations_match_a_btree_map() {
+        for seed in 1..=20 {
+            for &keys in [4, 64, 1000].iter() {
+                check_map(&mut Bst::new(), &random_map_ops(seed, 1000, keys)).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn removing_inner_nodes_matches_a_btree_map() {
+        // a balanced shape first, so removals hit nodes with two children
+        let mut ops: Vec<MapOp<u32, u32>> = [8, 4, 12, 2, 6, 10, 14, 1, 3, 5, 7, 9, 11, 13, 15]
+            .iter()
+            .map(|&key| MapOp::Insert(key, key * 10))
+            .collect();
+        ops.extend([8, 4, 12, 6, 1, 8, 15].iter().map(|&key| MapOp::Remove(key)));
+        ops.extend((0..17).map(MapOp::Get));
+        check_map(&mut Bst::new(), &ops).unwrap();
+    }
+
+    #[test]
+    fn min_max_and_iteration_follow_the_key_order() {
+        let mut tree = Bst::new();
+        let mut model = BTreeMap::new();
+        for op in random_map_ops(99, 500, 200) {
+            if let MapOp::Insert(key, value) = op {
+                tree.insert(key, value);
+                model.insert(key, value);
+            }
+        }
+        assert_eq!(tree.min(), model.iter().next());
+        assert_eq!(tree.max(), model.iter().next_back());
+        assert!(tree.iter().eq(model.iter()));
+        assert_eq!(tree.len(), model.len());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn sorted_inserts_build_a_degenerate_tree_that_still_drops() {
+        let tree: Bst<u32, ()> = (0..20_000).map(|key| (key, ())).collect();
+        assert_eq!(tree.height(), 20_000);
+        assert_eq!(tree.len(), 20_000);
+    }
+}
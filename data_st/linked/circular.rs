@@ -0,0 +1,300 @@
+//! Circular singly linked list, where the last node links back to the head
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<CircularNode<T>>>;
+
+struct CircularNode<T> {
+    data: T,
+    next: NonNull<CircularNode<T>>
+}
+
+
+/// Ring of elements. Only the last node is stored, the head is the node
+/// after it, so both ends and a rotation are O(1).
+pub struct Circular<T> {
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<CircularNode<T>>>
+}
+
+/// Goes once around the ring, from the head to the last element
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    remaining: usize,
+    marker: PhantomData<&'a CircularNode<T>>
+}
+
+/// Walks the ring without end, e.g. to pick every k-th element in a
+/// round robin or Josephus elimination
+pub struct CursorMut<'a, T> {
+    list: &'a mut Circular<T>,
+
+    /// Node before the current one, `None` only when the ring is empty
+    prev: Link<T>
+}
+
+
+impl<T> Circular<T> {
+    pub fn new() -> Self {
+        Circular {
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tail.is_none()
+    }
+
+    /// Inserts `data` as the new head, between the last node and the old head
+    pub fn push_front(&mut self, data: T) {
+        let node = self.insert_after_tail(data);
+        if self.tail.is_none() {
+            self.tail = Some(node);
+        }
+    }
+
+    /// Inserts `data` as the new last element
+    pub fn push_back(&mut self, data: T) {
+        let node = self.insert_after_tail(data);
+        self.tail = Some(node);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.tail.map(|tail| unsafe { self.unlink_after(tail) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.tail.map(|tail| unsafe { &(*(*tail.as_ptr()).next.as_ptr()).data })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|tail| unsafe { &(*tail.as_ptr()).data })
+    }
+
+    /// Moves the head to the back in O(1); the next element becomes the head
+    pub fn rotate(&mut self) {
+        if let Some(tail) = self.tail {
+            self.tail = Some(unsafe { (*tail.as_ptr()).next });
+        }
+    }
+
+    /// Rotates `n` times, walking `n` modulo the length
+    pub fn rotate_by(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        for _ in 0..n % self.len {
+            self.rotate();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Cursor starting on the head
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            prev: self.tail,
+            list: self
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.tail.map(|tail| unsafe { (*tail.as_ptr()).next }),
+            remaining: self.len,
+            marker: PhantomData
+        }
+    }
+
+    /// Links a new node in after the last one, closing the ring if it was empty
+    fn insert_after_tail(&mut self, data: T) -> NonNull<CircularNode<T>> {
+        let node = new_node(data);
+        unsafe {
+            match self.tail {
+                Some(tail) => insert_after(tail, node),
+                None => (*node.as_ptr()).next = node,
+            }
+        }
+        self.len += 1;
+        node
+    }
+
+    /// Unlinks and frees the node after `prev`, a live node of this ring
+    unsafe fn unlink_after(&mut self, prev: NonNull<CircularNode<T>>) -> T {
+        let node = (*prev.as_ptr()).next;
+        if node == prev {
+            self.tail = None;
+        } else {
+            (*prev.as_ptr()).next = (*node.as_ptr()).next;
+            if Some(node) == self.tail {
+                self.tail = Some(prev);
+            }
+        }
+        self.len -= 1;
+        Box::from_raw(node.as_ptr()).data
+    }
+}
+
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.prev.map(|prev| unsafe { &mut (*(*prev.as_ptr()).next.as_ptr()).data })
+    }
+
+    /// Moves to the next element, from the last one back to the head
+    pub fn move_next(&mut self) {
+        if let Some(prev) = self.prev {
+            self.prev = Some(unsafe { (*prev.as_ptr()).next });
+        }
+    }
+
+    /// Inserts `data` after the current element and stays on the current
+    /// one. On an empty ring the new element becomes the current one.
+    pub fn insert_after(&mut self, data: T) {
+        let prev = match self.prev {
+            Some(prev) => prev,
+            None => {
+                self.list.push_back(data);
+                self.prev = self.list.tail;
+                return;
+            }
+        };
+
+        unsafe {
+            let current = (*prev.as_ptr()).next;
+            let node = new_node(data);
+            insert_after(current, node);
+            if Some(current) == self.list.tail {
+                self.list.tail = Some(node);
+            }
+            // On a ring of one the current node was its own predecessor
+            if current == prev {
+                self.prev = Some(node);
+            }
+        }
+        self.list.len += 1;
+    }
+
+    /// Unlinks the current element and returns it, moving on to the
+    /// element after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let prev = self.prev?;
+        let data = unsafe { self.list.unlink_after(prev) };
+        if self.list.tail.is_none() {
+            self.prev = None;
+        }
+        Some(data)
+    }
+}
+
+
+fn new_node<T>(data: T) -> NonNull<CircularNode<T>> {
+    let node = Box::new(CircularNode {
+        data,
+        next: NonNull::dangling()
+    });
+    NonNull::from(Box::leak(node))
+}
+
+/// Links `node` in after `prev`, a live node of a ring
+unsafe fn insert_after<T>(prev: NonNull<CircularNode<T>>, node: NonNull<CircularNode<T>>) {
+    (*node.as_ptr()).next = (*prev.as_ptr()).next;
+    (*prev.as_ptr()).next = node;
+}
+
+
+// The ring owns its nodes, so it is as thread safe as its elements
+unsafe impl<T: Send> Send for Circular<T> {}
+
+unsafe impl<T: Sync> Sync for Circular<T> {}
+
+// The iterator and the cursor borrow the ring, so they are as thread safe
+// as the references to the elements they hand out
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+impl<T> Drop for Circular<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for Circular<T> {
+    fn default() -> Self {
+        Circular::new()
+    }
+}
+
+impl<T: Clone> Clone for Circular<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Circular<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for Circular<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Circular::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for Circular<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.remaining -= 1;
+            self.next = Some(node.next);
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Circular<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,292 @@
+//! Lock-free stack that can be shared between threads
+
+use std::mem::ManuallyDrop;
+use std::ptr;
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+// RUSTFLAGS="--cfg loom" swaps in loom's atomics to model check the stack
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+struct StackNode<T> {
+    /// Moved out by the `pop` that unlinks the node
+    data: ManuallyDrop<T>,
+
+    /// Next node on the stack, or on the list of nodes to free once the
+    /// node was popped. Atomic since a stale `pop` may still read it.
+    next: AtomicPtr<StackNode<T>>
+}
+
+
+/// Treiber stack: `push` and `pop` swing the head with a compare and swap.
+///
+/// A popped node can't be freed right away, since a concurrent `pop` may
+/// still be reading it. Nodes are freed by the last thread leaving `pop`,
+/// otherwise they wait on a list that the next quiet `pop` frees. Addresses
+/// aren't reused while any `pop` could hold them, which also rules out ABA.
+pub struct ConcurrentStack<T> {
+    head: AtomicPtr<StackNode<T>>,
+
+    /// Threads currently inside `pop`
+    poppers: AtomicUsize,
+
+    /// Popped nodes waiting to be freed, chained through `next`
+    retired: AtomicPtr<StackNode<T>>
+}
+
+
+impl<T> ConcurrentStack<T> {
+    pub fn new() -> Self {
+        ConcurrentStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            poppers: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut())
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    pub fn push(&self, data: T) {
+        let node = Box::into_raw(Box::new(StackNode {
+            data: ManuallyDrop::new(data),
+            next: AtomicPtr::new(ptr::null_mut())
+        }));
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // the node isn't shared until the exchange succeeds
+            unsafe { (*node).next.store(head, Ordering::Relaxed) };
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.poppers.fetch_add(1, Ordering::SeqCst);
+
+        // SeqCst orders this load after the increment, and a winning exchange
+        // before retire reads the count. Otherwise a pop counted too late to
+        // stop a node being freed could still load it as the head.
+        let mut head = self.head.load(Ordering::SeqCst);
+        while !head.is_null() {
+            // head can't have been freed, this thread is counted in poppers
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            match self.head.compare_exchange_weak(head, next, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        if head.is_null() {
+            self.poppers.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+
+        // only the thread whose exchange unlinked the node gets here for it
+        let data = unsafe { ManuallyDrop::take(&mut (*head).data) };
+        unsafe { self.retire(head) };
+        Some(data)
+    }
+
+    /// Frees `node`, already unlinked and emptied, or defers it to a
+    /// later `pop`, then leaves the count of poppers
+    unsafe fn retire(&self, node: *mut StackNode<T>) {
+        if self.poppers.load(Ordering::SeqCst) == 1 {
+            // no other pop started before the node was unlinked
+            let retired = self.retired.swap(ptr::null_mut(), Ordering::SeqCst);
+            if self.poppers.fetch_sub(1, Ordering::SeqCst) == 1 {
+                free_chain(retired);
+            } else if !retired.is_null() {
+                self.defer_chain(retired);
+            }
+            drop(Box::from_raw(node));
+        } else {
+            (*node).next.store(ptr::null_mut(), Ordering::Relaxed);
+            self.defer_chain(node);
+            self.poppers.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Puts a chain of popped nodes back on the list to free
+    unsafe fn defer_chain(&self, first: *mut StackNode<T>) {
+        let mut last = first;
+        loop {
+            let next = (*last).next.load(Ordering::Relaxed);
+            if next.is_null() {
+                break;
+            }
+            last = next;
+        }
+
+        let mut retired = self.retired.load(Ordering::Relaxed);
+        loop {
+            (*last).next.store(retired, Ordering::Relaxed);
+            match self.retired.compare_exchange_weak(retired, first, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => retired = current,
+            }
+        }
+    }
+}
+
+
+/// Frees a chain of popped nodes, whose data was already moved out
+unsafe fn free_chain<T>(mut node: *mut StackNode<T>) {
+    while !node.is_null() {
+        let next = (*node).next.load(Ordering::Relaxed);
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+
+// Elements move between threads through push and pop, and are never
+// shared, so the stack only needs them to be Send
+unsafe impl<T: Send> Send for ConcurrentStack<T> {}
+
+unsafe impl<T: Send> Sync for ConcurrentStack<T> {}
+
+impl<T> Drop for ConcurrentStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+        // `&mut self` rules out other threads, loom's atomics have no get_mut
+        unsafe { free_chain(self.retired.load(Ordering::Acquire)) };
+    }
+}
+
+impl<T> Default for ConcurrentStack<T> {
+    fn default() -> Self {
+        ConcurrentStack::new()
+    }
+}
+
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use super::*;
+
+    const THREADS: usize = 8;
+    const PER_THREAD: usize = if cfg!(miri) { 200 } else { 20_000 };
+
+    #[test]
+    fn every_element_comes_out_exactly_once() {
+        let stack = Arc::new(ConcurrentStack::new());
+        let barrier = Arc::new(Barrier::new(THREADS));
+
+        let workers: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let stack = Arc::clone(&stack);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    let mut popped = Vec::new();
+                    for i in 0..PER_THREAD {
+                        stack.push(thread * PER_THREAD + i);
+                        // pop about half as often, so pops race with pushes
+                        // and with each other on a stack that is rarely empty
+                        if i % 2 == 1 {
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen: Vec<usize> = workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect();
+        while let Some(data) = stack.pop() {
+            seen.push(data);
+        }
+        assert!(stack.is_empty());
+
+        seen.sort_unstable();
+        assert!(seen.into_iter().eq(0..THREADS * PER_THREAD));
+    }
+
+    #[test]
+    fn dropping_a_shared_stack_drops_what_is_left() {
+        let stack = Arc::new(ConcurrentStack::new());
+        let counter = Arc::new(());
+        let rounds = if cfg!(miri) { 100 } else { 1000 };
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let stack = Arc::clone(&stack);
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || {
+                    for _ in 0..rounds {
+                        stack.push(Arc::clone(&counter));
+                        stack.push(Arc::clone(&counter));
+                        drop(stack.pop());
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(Arc::strong_count(&counter), 1 + 4 * rounds);
+        drop(stack);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+}
+
+// RUSTFLAGS="--cfg loom" cargo test --release --lib concurrent
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::sync::Arc;
+    use loom::thread;
+
+    use super::*;
+
+    #[test]
+    fn concurrent_pops_take_each_element_once() {
+        loom::model(|| {
+            let stack = Arc::new(ConcurrentStack::new());
+            stack.push(1);
+            stack.push(2);
+
+            let other = {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.pop())
+            };
+            let mine = stack.pop();
+            let theirs = other.join().unwrap();
+
+            let mut popped: Vec<_> = mine.into_iter().chain(theirs).collect();
+            popped.sort_unstable();
+            assert_eq!(popped, [1, 2]);
+            assert!(stack.pop().is_none());
+        });
+    }
+
+    #[test]
+    fn a_push_racing_a_pop_is_not_lost() {
+        loom::model(|| {
+            let stack = Arc::new(ConcurrentStack::new());
+            stack.push(1);
+
+            let pusher = {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.push(2))
+            };
+            let first = stack.pop();
+            pusher.join().unwrap();
+
+            let mut popped: Vec<_> = first.into_iter().collect();
+            while let Some(data) = stack.pop() {
+                popped.push(data);
+            }
+            popped.sort_unstable();
+            assert_eq!(popped, [1, 2]);
+        });
+    }
+}
@@ -0,0 +1,672 @@
+//! Doubly linked list with O(1) operations at both ends
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+use crate::memory::MemoryFootprint;
+
+type Link<T> = Option<NonNull<DoublyNode<T>>>;
+
+struct DoublyNode<T> {
+    data: T,
+    prev: Link<T>,
+    next: Link<T>
+}
+
+
+/// List linked in both directions. Every node is owned by the list and
+/// freed when it is popped, removed or the list is dropped.
+pub struct DoublyLinked<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<DoublyNode<T>>>
+}
+
+/// Refers to an element pushed onto a `DoublyLinked`, so it can later be
+/// removed in O(1). A handle is only valid while its element is in the list.
+pub struct NodeHandle<T>(NonNull<DoublyNode<T>>);
+
+/// Owning iterator, from the head to the last element
+pub struct IntoIter<T>(DoublyLinked<T>);
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a DoublyNode<T>>
+}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut DoublyNode<T>>
+}
+
+/// Edits a list in place while walking it in either direction. Between
+/// the last element and the head the cursor sits on a "ghost" position.
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinked<T>,
+
+    /// `None` on the ghost position
+    current: Link<T>,
+
+    /// Index of the current element, the length on the ghost position
+    index: usize
+}
+
+
+impl<T> DoublyLinked<T> {
+    pub fn new() -> Self {
+        DoublyLinked {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Pushes `data` at the head in O(1)
+    pub fn push_front(&mut self, data: T) -> NodeHandle<T> {
+        let node = new_node(data, None, self.head);
+        match self.head {
+            Some(head) => unsafe { (*head.as_ptr()).prev = Some(node) },
+            None => self.tail = Some(node),
+        }
+        self.head = Some(node);
+        self.len += 1;
+        NodeHandle(node)
+    }
+
+    /// Pushes `data` after the last element in O(1)
+    pub fn push_back(&mut self, data: T) -> NodeHandle<T> {
+        let node = new_node(data, self.tail, None);
+        match self.tail {
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(node);
+        self.len += 1;
+        NodeHandle(node)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head| unsafe { self.unlink(head) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|tail| unsafe { self.unlink(tail) })
+    }
+
+    /// Removes the element `handle` refers to in O(1)
+    ///
+    /// # Safety
+    ///
+    /// `handle` must come from this list and its element must not have
+    /// been popped or removed since.
+    pub unsafe fn remove(&mut self, handle: NodeHandle<T>) -> T {
+        self.unlink(handle.0)
+    }
+
+    /// Element `handle` refers to
+    ///
+    /// # Safety
+    ///
+    /// Same as `remove`.
+    pub unsafe fn get(&self, handle: NodeHandle<T>) -> &T {
+        &(*handle.0.as_ptr()).data
+    }
+
+    /// # Safety
+    ///
+    /// Same as `remove`.
+    pub unsafe fn get_mut(&mut self, handle: NodeHandle<T>) -> &mut T {
+        &mut (*handle.0.as_ptr()).data
+    }
+
+    /// Moves the element `handle` refers to after the last element in
+    /// O(1), without reallocating it
+    ///
+    /// # Safety
+    ///
+    /// Same as `remove`.
+    pub unsafe fn move_to_back(&mut self, handle: NodeHandle<T>) {
+        let node = handle.0;
+        if self.tail == Some(node) {
+            return;
+        }
+        match (*node.as_ptr()).prev {
+            Some(prev) => (*prev.as_ptr()).next = (*node.as_ptr()).next,
+            None => self.head = (*node.as_ptr()).next,
+        }
+        // not the tail, so there is a next node
+        if let Some(next) = (*node.as_ptr()).next {
+            (*next.as_ptr()).prev = (*node.as_ptr()).prev;
+        }
+        (*node.as_ptr()).prev = self.tail;
+        (*node.as_ptr()).next = None;
+        if let Some(tail) = self.tail {
+            (*tail.as_ptr()).next = Some(node);
+        }
+        self.tail = Some(node);
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|head| unsafe { &(*head.as_ptr()).data })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| unsafe { &mut (*head.as_ptr()).data })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|tail| unsafe { &(*tail.as_ptr()).data })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|tail| unsafe { &mut (*tail.as_ptr()).data })
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Cursor starting on the head, or on the ghost position if empty
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self
+        }
+    }
+
+    /// Cursor starting on the last element, or on the ghost position if empty
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self
+        }
+    }
+
+    /// Iterates from the head; `rev()` iterates from the last element
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData
+        }
+    }
+
+    /// Detaches `node` from its neighbours and frees it.
+    /// `node` must be a live node of this list.
+    unsafe fn unlink(&mut self, node: NonNull<DoublyNode<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.data
+    }
+}
+
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Index of the current element, `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Moves to the next element, from the last one to the ghost
+    /// position, and from the ghost position to the head
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Moves to the previous element, from the head to the ghost
+    /// position, and from the ghost position to the last element
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = match self.current {
+                    Some(_) => self.index - 1,
+                    None => self.list.len,
+                };
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Inserts `data` before the current element and stays on the
+    /// current one. On the ghost position this pushes at the back.
+    pub fn insert_before(&mut self, data: T) {
+        let current = match self.current {
+            Some(node) => node,
+            None => {
+                self.list.push_back(data);
+                self.index = self.list.len;
+                return;
+            }
+        };
+
+        unsafe {
+            let prev = (*current.as_ptr()).prev;
+            let node = new_node(data, prev, Some(current));
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.list.head = Some(node),
+            }
+            (*current.as_ptr()).prev = Some(node);
+        }
+        self.index += 1;
+        self.list.len += 1;
+    }
+
+    /// Inserts `data` after the current element and stays on the
+    /// current one. On the ghost position this pushes at the head.
+    pub fn insert_after(&mut self, data: T) {
+        let current = match self.current {
+            Some(node) => node,
+            None => {
+                self.list.push_front(data);
+                self.index = self.list.len;
+                return;
+            }
+        };
+
+        unsafe {
+            let next = (*current.as_ptr()).next;
+            let node = new_node(data, Some(current), next);
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.list.tail = Some(node),
+            }
+            (*current.as_ptr()).next = Some(node);
+        }
+        self.list.len += 1;
+    }
+
+    /// Unlinks the current element and returns it, moving on to the
+    /// element after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let current = self.current?;
+        unsafe {
+            self.current = (*current.as_ptr()).next;
+            Some(self.list.unlink(current))
+        }
+    }
+
+    /// Moves every element after the current one into a new list. On the
+    /// ghost position the whole list is moved.
+    pub fn split_after(&mut self) -> DoublyLinked<T> {
+        let current = match self.current {
+            Some(node) => node,
+            None => {
+                self.index = 0;
+                return std::mem::take(self.list);
+            }
+        };
+
+        let mut rest = DoublyLinked::new();
+        unsafe {
+            if let Some(next) = (*current.as_ptr()).next.take() {
+                (*next.as_ptr()).prev = None;
+                rest.head = Some(next);
+                rest.tail = self.list.tail;
+                rest.len = self.list.len - self.index - 1;
+                self.list.tail = Some(current);
+                self.list.len = self.index + 1;
+            }
+        }
+        rest
+    }
+}
+
+
+fn new_node<T>(data: T, prev: Link<T>, next: Link<T>) -> NonNull<DoublyNode<T>> {
+    let node = Box::new(DoublyNode {
+        data,
+        prev,
+        next
+    });
+    NonNull::from(Box::leak(node))
+}
+
+
+// The list owns its nodes, so it is as thread safe as its elements
+unsafe impl<T: Send> Send for DoublyLinked<T> {}
+
+unsafe impl<T: Sync> Sync for DoublyLinked<T> {}
+
+// A handle is only dereferenced through the list it came from
+unsafe impl<T: Send> Send for NodeHandle<T> {}
+
+unsafe impl<T: Sync> Sync for NodeHandle<T> {}
+
+// The iterators and the cursor borrow the list, so they are as thread
+// safe as the references to the elements they hand out
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+impl<T> Drop for DoublyLinked<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for DoublyLinked<T> {
+    fn heap_size(&self) -> usize {
+        let nodes = self.len * mem::size_of::<DoublyNode<T>>();
+        nodes + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> Default for DoublyLinked<T> {
+    fn default() -> Self {
+        DoublyLinked::new()
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinked<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinked<T> {}
+
+impl<T: PartialOrd> PartialOrd for DoublyLinked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for DoublyLinked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for DoublyLinked<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for data in self.iter() {
+            data.hash(state);
+        }
+    }
+}
+
+/// Formats the list as `[1 <-> 2 <-> 3]`
+impl<T: fmt::Display> fmt::Display for DoublyLinked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, data) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " <-> ")?;
+            }
+            write!(f, "{}", data)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinked<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinked::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for DoublyLinked<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for DoublyLinked<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<DoublyLinked<T>> for Vec<T> {
+    fn from(list: DoublyLinked<T>) -> Self {
+        let mut vec = Vec::with_capacity(list.len());
+        vec.extend(list);
+        vec
+    }
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeHandle<T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.data
+        })
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &mut node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &mut node.data
+        })
+    }
+}
+
+impl<T> IntoIterator for DoublyLinked<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinked<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinked<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn converts_to_and_from_a_vec() {
+        let list = DoublyLinked::from(vec![1, 2, 3]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.back(), Some(&3));
+        assert_eq!(Vec::from(list), vec![1, 2, 3]);
+        assert_eq!(Vec::from(DoublyLinked::<u32>::from(Vec::new())), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn compares_and_hashes_like_a_vec() {
+        let lists: Vec<Vec<u32>> = vec![vec![], vec![1], vec![1, 2], vec![1, 3], vec![2]];
+        for a in lists.iter() {
+            for b in lists.iter() {
+                let (list_a, list_b) = (DoublyLinked::from(a.clone()), DoublyLinked::from(b.clone()));
+                assert_eq!(list_a.cmp(&list_b), a.cmp(b));
+                assert_eq!(list_a.partial_cmp(&list_b), a.partial_cmp(b));
+                assert_eq!(hash_of(&list_a) == hash_of(&list_b), a == b);
+            }
+        }
+        assert_eq!(DoublyLinked::from(vec![1.0, f64::NAN]).partial_cmp(&DoublyLinked::from(vec![1.0, 2.0])), None);
+    }
+
+    #[test]
+    fn displays_the_elements_in_order() {
+        assert_eq!(DoublyLinked::from(vec![1, 2, 3]).to_string(), "[1 <-> 2 <-> 3]");
+        assert_eq!(DoublyLinked::from(vec!["a"]).to_string(), "[a]");
+        assert_eq!(DoublyLinked::<u32>::new().to_string(), "[]");
+    }
+}
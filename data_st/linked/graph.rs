@@ -0,0 +1,295 @@
+//! Graphs stored as adjacency lists, with traversals and shortest paths
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::Add;
+
+/// Index of a node, in the order nodes were added
+pub type NodeId = usize;
+
+struct Edge<E> {
+    from: NodeId,
+    to: NodeId,
+    weight: E
+}
+
+
+/// Graph with node weights `N` and edge weights `E`. An undirected edge
+/// is stored once and listed in the adjacency of both its ends.
+pub struct Graph<N, E> {
+    nodes: Vec<N>,
+    edges: Vec<Edge<E>>,
+
+    /// For every node, the neighbours it reaches and the edge leading there
+    adjacency: Vec<Vec<(NodeId, usize)>>,
+
+    directed: bool
+}
+
+/// Breadth first traversal, yielding every node reachable from the start
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    queue: VecDeque<NodeId>,
+    seen: Vec<bool>
+}
+
+/// Depth first traversal in preorder, visiting neighbours in the order
+/// their edges were added
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    stack: Vec<NodeId>,
+    seen: Vec<bool>
+}
+
+/// Distances from one node, as computed by `Graph::dijkstra`
+pub struct ShortestPaths<E> {
+    start: NodeId,
+    distances: Vec<Option<E>>,
+    previous: Vec<Option<NodeId>>
+}
+
+
+impl<N, E> Graph<N, E> {
+    pub fn new_directed() -> Self {
+        Graph::new(true)
+    }
+
+    pub fn new_undirected() -> Self {
+        Graph::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Graph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            adjacency: Vec::new(),
+            directed
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.nodes.push(weight);
+        self.adjacency.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: E) -> Result<(), &'static str> {
+        if from >= self.nodes.len() || to >= self.nodes.len() {
+            return Err("Edge refers to an unknown node");
+        }
+        let edge = self.edges.len();
+        self.edges.push(Edge {
+            from,
+            to,
+            weight
+        });
+        self.adjacency[from].push((to, edge));
+        if !self.directed && from != to {
+            self.adjacency[to].push((from, edge));
+        }
+        Ok(())
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id)
+    }
+
+    /// Nodes reachable from `id` over one edge, with that edge's weight
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = (NodeId, &E)> + '_ {
+        self.adjacency.get(id)
+            .into_iter()
+            .flatten()
+            .map(move |&(to, edge)| (to, &self.edges[edge].weight))
+    }
+
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, E> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        if start < self.nodes.len() {
+            seen[start] = true;
+            queue.push_back(start);
+        }
+        Bfs {
+            graph: self,
+            queue,
+            seen
+        }
+    }
+
+    pub fn dfs(&self, start: NodeId) -> Dfs<'_, N, E> {
+        let mut stack = Vec::new();
+        if start < self.nodes.len() {
+            stack.push(start);
+        }
+        Dfs {
+            graph: self,
+            stack,
+            seen: vec![false; self.nodes.len()]
+        }
+    }
+
+    /// Orders the nodes so every edge points forward, using Kahn's
+    /// algorithm. Fails on undirected graphs and graphs with a cycle.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, &'static str> {
+        if !self.directed {
+            return Err("Only directed graphs have a topological order");
+        }
+
+        let mut incoming = vec![0usize; self.nodes.len()];
+        for edge in self.edges.iter() {
+            incoming[edge.to] += 1;
+        }
+        let mut ready: VecDeque<NodeId> = (0..self.nodes.len()).filter(|&id| incoming[id] == 0).collect();
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for &(to, _) in self.adjacency[id].iter() {
+                incoming[to] -= 1;
+                if incoming[to] == 0 {
+                    ready.push_back(to);
+                }
+            }
+        }
+
+        if order.len() < self.nodes.len() {
+            return Err("Graph has a cycle");
+        }
+        Ok(order)
+    }
+
+    /// Whether some path leads from a node back to itself. A self loop
+    /// counts, and so do two edges between the same undirected pair.
+    pub fn has_cycle(&self) -> bool {
+        if self.directed {
+            return self.topological_sort().is_err();
+        }
+
+        // union find: an edge joining two nodes already connected closes a cycle
+        let mut parent: Vec<NodeId> = (0..self.nodes.len()).collect();
+        fn root(parent: &mut [NodeId], mut id: NodeId) -> NodeId {
+            while parent[id] != id {
+                parent[id] = parent[parent[id]];
+                id = parent[id];
+            }
+            id
+        }
+        for edge in self.edges.iter() {
+            let (a, b) = (root(&mut parent, edge.from), root(&mut parent, edge.to));
+            if a == b {
+                return true;
+            }
+            parent[a] = b;
+        }
+        false
+    }
+
+    /// Shortest distances from `start` by Dijkstra's algorithm. Edge
+    /// weights must not be negative; `E::default()` is the zero distance.
+    pub fn dijkstra(&self, start: NodeId) -> ShortestPaths<E>
+    where
+        E: Copy + Ord + Default + Add<Output = E>,
+    {
+        let mut distances: Vec<Option<E>> = vec![None; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        let mut heap = BinaryHeap::new();
+        if start < self.nodes.len() {
+            distances[start] = Some(E::default());
+            heap.push(Reverse((E::default(), start)));
+        }
+
+        while let Some(Reverse((distance, id))) = heap.pop() {
+            if distances[id].is_some_and(|best| distance > best) {
+                continue;
+            }
+            for &(to, edge) in self.adjacency[id].iter() {
+                let candidate = distance + self.edges[edge].weight;
+                if distances[to].is_none_or(|best| candidate < best) {
+                    distances[to] = Some(candidate);
+                    previous[to] = Some(id);
+                    heap.push(Reverse((candidate, to)));
+                }
+            }
+        }
+
+        ShortestPaths {
+            start,
+            distances,
+            previous
+        }
+    }
+}
+
+
+impl<E: Copy> ShortestPaths<E> {
+    /// Length of the shortest path to `to`, `None` if it can't be reached
+    pub fn distance(&self, to: NodeId) -> Option<E> {
+        self.distances.get(to).copied().flatten()
+    }
+
+    /// Nodes of the shortest path to `to`, from the start node to `to`
+    pub fn path(&self, to: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(to)?;
+        let mut path = vec![to];
+        let mut current = to;
+        while current != self.start {
+            current = self.previous[current]?;
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+
+impl<'a, N, E> Iterator for Bfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.queue.pop_front()?;
+        for &(to, _) in self.graph.adjacency[id].iter() {
+            if !self.seen[to] {
+                self.seen[to] = true;
+                self.queue.push_back(to);
+            }
+        }
+        Some(id)
+    }
+}
+
+impl<'a, N, E> Iterator for Dfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        while let Some(id) = self.stack.pop() {
+            if self.seen[id] {
+                continue;
+            }
+            self.seen[id] = true;
+            // pushed in reverse so the first neighbour is visited first
+            for &(to, _) in self.graph.adjacency[id].iter().rev() {
+                if !self.seen[to] {
+                    self.stack.push(to);
+                }
+            }
+            return Some(id);
+        }
+        None
+    }
+}
@@ -0,0 +1,282 @@
+//! Hash map with separate chaining, each bucket being a `Linked` list
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+use crate::memory::MemoryFootprint;
+use crate::Linked;
+
+/// Buckets of an empty table before its first resize
+const INITIAL_BUCKETS: usize = 8;
+
+
+/// Hash map whose keys are spread over buckets by `S`. Colliding entries
+/// share a bucket's list, and the bucket count doubles once the table
+/// holds more than three entries per four buckets.
+pub struct HashTable<K, V, S = RandomState> {
+    buckets: Vec<Linked<(K, V)>>,
+    len: usize,
+    hasher: S
+}
+
+pub struct Iter<'a, K, V> {
+    buckets: std::slice::Iter<'a, Linked<(K, V)>>,
+    entries: Option<crate::Iter<'a, (K, V)>>,
+    remaining: usize
+}
+
+
+impl<K: Hash + Eq, V> HashTable<K, V, RandomState> {
+    pub fn new() -> Self {
+        HashTable::with_hasher(RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashTable<K, V, S> {
+    /// Empty table hashing its keys with `hasher`
+    pub fn with_hasher(hasher: S) -> Self {
+        HashTable {
+            buckets: Vec::new(),
+            len: 0,
+            hasher
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of buckets
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // replacing a value doesn't grow the table, so it must not resize
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        if (self.len + 1) * 4 > self.buckets.len() * 3 {
+            self.resize();
+        }
+
+        let bucket = self.bucket_of(&key);
+        self.buckets[bucket].add((key, value));
+        self.len += 1;
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        if self.len == 0 {
+            return None;
+        }
+        self.buckets[self.bucket_of(key)].iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.len == 0 {
+            return None;
+        }
+        let bucket = self.bucket_of(key);
+        self.buckets[bucket].iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if self.len == 0 {
+            return None;
+        }
+        let bucket = self.bucket_of(key);
+        let (_, value) = self.buckets[bucket].remove_first_where(|(k, _)| k == key)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn clear(&mut self) {
+        for chain in self.buckets.iter_mut() {
+            chain.clear();
+        }
+        self.len = 0;
+    }
+
+    /// Entries in no particular order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            entries: None,
+            remaining: self.len
+        }
+    }
+
+    fn bucket_of(&self, key: &K) -> usize {
+        // the bucket count is a power of two
+        (self.hasher.hash_one(key) as usize) & (self.buckets.len() - 1)
+    }
+
+    /// Doubles the buckets and moves every entry to its new bucket
+    fn resize(&mut self) {
+        let count = (self.buckets.len() * 2).max(INITIAL_BUCKETS);
+        let old = std::mem::replace(&mut self.buckets, (0..count).map(|_| Linked::new()).collect());
+        for chain in old {
+            for (key, value) in chain {
+                let bucket = self.bucket_of(&key);
+                self.buckets[bucket].add((key, value));
+            }
+        }
+    }
+}
+
+
+impl<K: MemoryFootprint, V: MemoryFootprint, S> MemoryFootprint for HashTable<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.buckets.heap_size()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for HashTable<K, V, S> {
+    fn default() -> Self {
+        HashTable::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq + fmt::Debug, V: fmt::Debug, S: BuildHasher> fmt::Debug for HashTable<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashTable<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut table = HashTable::default();
+        table.extend(iter);
+        table
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashTable<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            if let Some((key, value)) = self.entries.as_mut().and_then(|entries| entries.next()) {
+                self.remaining -= 1;
+                return Some((key, value));
+            }
+            self.entries = Some(self.buckets.next()?.iter());
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a HashTable<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    use super::*;
+    use crate::testing::{check_map, random_map_ops};
+
+    /// Sends every key to the same bucket
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    type Colliding<K, V> = HashTable<K, V, BuildHasherDefault<ConstantHasher>>;
+
+    #[test]
+    fn colliding_keys_share_a_bucket_and_stay_apart() {
+        let mut table: Colliding<u32, u32> = HashTable::with_hasher(BuildHasherDefault::default());
+        for key in 0..100 {
+            assert_eq!(table.insert(key, key * 2), None);
+        }
+        assert_eq!(table.insert(50, 0), Some(100));
+        assert_eq!(table.remove(&51), Some(102));
+        assert_eq!(table.remove(&51), None);
+
+        assert_eq!(table.len(), 99);
+        assert_eq!(table.buckets[0].len(), 99);
+        assert_eq!(table.get(&50), Some(&0));
+        assert!((0..100).filter(|&key| key != 50 && key != 51).all(|key| table.get(&key) == Some(&(key * 2))));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn colliding_keys_match_a_btree_map() {
+        for seed in 1..=5 {
+            let mut table: Colliding<u32, u32> = HashTable::with_hasher(BuildHasherDefault::default());
+            check_map(&mut table, &random_map_ops(seed, 1000, 40)).unwrap();
+        }
+    }
+
+    #[test]
+    fn buckets_double_past_three_quarters_load() {
+        let mut table = HashTable::new();
+        assert_eq!(table.buckets(), 0);
+        for key in 0..1000u32 {
+            let before = table.buckets();
+            table.insert(key, key);
+
+            let needed = (key as usize + 1) * 4 > before * 3;
+            let expected = if needed { (before * 2).max(INITIAL_BUCKETS) } else { before };
+            assert_eq!(table.buckets(), expected, "after inserting {}", key);
+            assert!(table.len() * 4 <= table.buckets() * 3);
+        }
+        assert!((0..1000).all(|key| table.get(&key) == Some(&key)));
+    }
+
+    #[test]
+    fn replacing_a_value_does_not_resize() {
+        let mut table = HashTable::new();
+        for key in 0..6u32 {
+            table.insert(key, 0);
+        }
+        // six entries fill eight buckets to the limit
+        assert_eq!(table.buckets(), 8);
+        for key in 0..6 {
+            assert_eq!(table.insert(key, 1), Some(0));
+        }
+        assert_eq!(table.buckets(), 8);
+        assert_eq!(table.len(), 6);
+    }
+}
@@ -1,63 +1,1503 @@
-pub fn welcome(){
-    println!("Linked list call");
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::marker::PhantomData;
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
+use std::ptr::NonNull;
+
+pub mod arena;
+pub mod arena_bst;
+pub mod arena_list;
+pub mod bloom;
+pub mod bst;
+pub mod circular;
+pub mod concurrent;
+pub mod doubly;
+pub mod graph;
+pub mod hash_table;
+pub mod lru;
+pub mod memory;
+pub mod merkle;
+pub mod persistent;
+pub mod queue;
+pub mod range_tree;
+#[cfg(feature = "raw")]
+pub mod raw;
+pub mod ring;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod skip_list;
+pub mod stack;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod thread_safety;
+pub mod traversal;
+pub mod trie;
+pub mod unrolled;
+
+pub use arena::Arena;
+pub use arena_bst::ArenaBst;
+pub use arena_list::ArenaList;
+pub use bloom::BloomFilter;
+pub use bst::Bst;
+pub use circular::Circular;
+pub use concurrent::ConcurrentStack;
+pub use doubly::DoublyLinked;
+pub use graph::Graph;
+pub use hash_table::HashTable;
+pub use lru::LruCache;
+pub use memory::MemoryFootprint;
+pub use merkle::MerkleTree;
+pub use persistent::PersistentList;
+pub use queue::Queue;
+pub use range_tree::{FenwickTree, SegmentTree};
+#[cfg(feature = "raw")]
+pub use raw::RawLinked;
+pub use ring::RingBuffer;
+pub use skip_list::SkipList;
+pub use stack::Stack;
+pub use traversal::Traversable;
+pub use trie::Trie;
+pub use unrolled::Unrolled;
+
+type Link<T> = Option<NonNull<LinkedNode<T>>>;
+
+struct LinkedNode<T> {
+    data: T,
+    next: Link<T>
 }
 
-#[derive(Clone,Debug)]
-struct LinkedNode {
-    data: i32,
-    next: Option<Box<LinkedNode>>
+
+/// Singly linked list. Every node is owned by the list through a raw
+/// pointer, as in `DoublyLinked`, so `tail` can alias the last one.
+pub struct Linked<T> {
+    head : Link<T>,
+
+    /// Last node of the chain starting at `head`, `None` when the list is empty
+    tail: Link<T>,
+
+    len: usize,
+    marker: PhantomData<Box<LinkedNode<T>>>
 }
 
+/// The list as it was before it became generic
+pub type LinkedI32 = Linked<i32>;
 
-#[derive(Clone,Debug)]
-pub struct Linked {
-    head : Option<LinkedNode>
+/// Owning iterator, from the head to the last element
+pub struct IntoIter<T>(Linked<T>);
+
+pub struct Iter<'a, T> {
+    next: Option<&'a LinkedNode<T>>
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut LinkedNode<T>>
 }
 
+/// Consecutive runs of elements, from `Linked::chunks`
+pub struct Chunks<'a, T> {
+    next: Option<&'a LinkedNode<T>>,
+    size: usize
+}
+
+/// View of up to `size` consecutive elements of a list
+pub struct Chunk<'a, T> {
+    head: &'a LinkedNode<T>,
+    len: usize
+}
+
+/// Removes a range of elements as it is iterated. Elements of the range
+/// that were not iterated are removed when it is dropped.
+pub struct Drain<'a, T> {
+    cursor: CursorMut<'a, T>,
+    remaining: usize
+}
+
+/// Edits a list in place while walking it. Past the last element the
+/// cursor sits on a "ghost" position, from which it wraps to the head.
+pub struct CursorMut<'a, T> {
+    list: &'a mut Linked<T>,
+
+    /// Node before the current one, `None` when the current one is the head
+    prev: Link<T>,
+
+    /// Index of the current element, the length on the ghost position
+    index: usize
+}
+
+
+impl<T> Linked<T> {
+    pub fn new() -> Self {
+        Linked {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    /// Pushes `data` at the head in O(1), moving the old head behind it
+    pub fn add(&mut self, data: T) {
+        let node = new_node(data, self.head);
+        if self.tail.is_none() {
+            self.tail = Some(node);
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Pushes `data` after the last element in O(1)
+    pub fn push_back(&mut self, data: T) {
+        self.push_back_node(new_node(data, None));
+    }
+
+    /// Links `node`, whose `next` is empty, after the last node
+    fn push_back_node(&mut self, node: NonNull<LinkedNode<T>>) {
+        match self.tail {
+            // the tail is a node of this list, which we borrow mutably
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    /// Removes the last element and returns it. The list only links
+    /// forwards, so finding the new last node walks the list in O(n).
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        if self.head == self.tail {
+            return self.pop();
+        }
+        let mut node = self.head?;
+        unsafe {
+            while (*node.as_ptr()).next != Some(tail) {
+                node = (*node.as_ptr()).next?;
+            }
+            (*node.as_ptr()).next = None;
+            self.tail = Some(node);
+            self.len -= 1;
+            Some(Box::from_raw(tail.as_ptr()).data)
+        }
+    }
+
+    /// Moves every element of `other` to the end of this list in O(1),
+    /// leaving `other` empty
+    pub fn append(&mut self, other: &mut Linked<T>) {
+        let head = match other.head.take() {
+            Some(head) => head,
+            None => return,
+        };
+        match self.tail {
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(head) },
+            None => self.head = Some(head),
+        }
+        self.tail = other.tail.take();
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Drops the head element in O(1)
+    pub fn remove(&mut self) {
+        self.pop();
+    }
+
+    /// Removes the head element and returns it
+    pub fn pop(&mut self) -> Option<T> {
+        // a node unlinked from the list is no longer aliased
+        self.pop_node().map(|node| unsafe { Box::from_raw(node.as_ptr()) }.data)
+    }
+
+    /// Unlinks the head node, leaving its `next` empty
+    fn pop_node(&mut self) -> Option<NonNull<LinkedNode<T>>> {
+        let node = self.head?;
+        self.head = unsafe { (*node.as_ptr()).next.take() };
+        if self.head.is_none() {
+            self.tail = None;
+        }
+        self.len -= 1;
+        Some(node)
+    }
+
+    /// Number of elements, kept up to date by every operation
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn contains(&self, data: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|x| x == data)
+    }
+
+    /// Drops every element, one node at a time
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
 
-impl LinkedNode {
-    fn new(dt : i32) -> Self {
-        LinkedNode {
-            data: dt,
-            next: None
+    /// Inserts `data` so that it ends up at `index`, shifting the
+    /// elements after it back.
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, data: T) {
+        if index > self.len {
+            panic!("insert index {} is out of bounds for a list of length {}", index, self.len);
         }
+        if index == 0 {
+            return self.add(data);
+        }
+        if index == self.len {
+            return self.push_back(data);
+        }
+
+        let prev = self.node_at(index - 1).unwrap();
+        unsafe {
+            (*prev.as_ptr()).next = Some(new_node(data, (*prev.as_ptr()).next));
+        }
+        self.len += 1;
     }
 
-    fn get_next(&mut self) -> Option<LinkedNode> {
-        if self.next.is_some() {
-            let nx = self.next.clone();
-            return Some(*nx.unwrap());
+    /// Removes the element at `index` and returns it, or `None` if
+    /// `index` is out of bounds
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        if index == 0 {
+            return self.pop();
+        }
+
+        let prev = self.node_at(index - 1).unwrap();
+        unsafe {
+            let removed = Box::from_raw((*prev.as_ptr()).next?.as_ptr());
+            (*prev.as_ptr()).next = removed.next;
+            if removed.next.is_none() {
+                self.tail = Some(prev);
+            }
+            self.len -= 1;
+            Some(removed.data)
+        }
+    }
+
+    /// Keeps only the elements `keep` returns true for, unlinking the
+    /// rest in a single pass
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut last = None;
+        let mut slot = &mut self.head;
+        while let Some(node) = *slot {
+            unsafe {
+                if keep(&(*node.as_ptr()).data) {
+                    last = Some(node);
+                    slot = &mut (*node.as_ptr()).next;
+                } else {
+                    *slot = (*node.as_ptr()).next;
+                    if slot.is_none() {
+                        self.tail = last;
+                    }
+                    self.len -= 1;
+                    drop(Box::from_raw(node.as_ptr()));
+                }
+            }
+        }
+    }
+
+    /// Unlinks the first element `pred` returns true for and returns it
+    pub fn remove_first_where<F>(&mut self, mut pred: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut last = None;
+        let mut slot = &mut self.head;
+        while let Some(node) = *slot {
+            unsafe {
+                if pred(&(*node.as_ptr()).data) {
+                    *slot = (*node.as_ptr()).next;
+                    if slot.is_none() {
+                        self.tail = last;
+                    }
+                    self.len -= 1;
+                    return Some(Box::from_raw(node.as_ptr()).data);
+                }
+                last = Some(node);
+                slot = &mut (*node.as_ptr()).next;
+            }
         }
         None
     }
+
+    /// Element at `index`, or `None` if it is out of bounds. Like
+    /// indexing, this walks `index` links.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.nth(index)
+    }
+
+    /// Element `n` places after the head, found by walking the links
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    /// First element, the same as `peek`
+    pub fn first(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut T> {
+        self.peek_mut()
+    }
+
+    /// Last element, in O(1) through the tail pointer
+    pub fn last(&self) -> Option<&T> {
+        // the tail is the last node, which the list owns
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        // as in `last`, and `&mut self` rules out any other borrow
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// First element from the head that `pred` holds for
+    pub fn find<P>(&self, mut pred: P) -> Option<&T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().find(|data| pred(data))
+    }
+
+    /// Mutable element at `index`, or `None` if it is out of bounds
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.node_at(index).map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Reverses the list in place by flipping the links
+    pub fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut next = self.head;
+        self.tail = self.head;
+        while let Some(node) = next {
+            unsafe {
+                next = (*node.as_ptr()).next;
+                (*node.as_ptr()).next = reversed;
+            }
+            reversed = Some(node);
+        }
+        self.head = reversed;
+    }
+
+    /// Splits the list in two at `index`. This list keeps the elements
+    /// before it and the rest are returned.
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn split_at(&mut self, index: usize) -> Linked<T> {
+        if index > self.len {
+            panic!("split index {} is out of bounds for a list of length {}", index, self.len);
+        }
+        if index == 0 {
+            return std::mem::take(self);
+        }
+
+        let last = self.node_at(index - 1).unwrap();
+        let mut rest = Linked::new();
+        rest.head = unsafe { (*last.as_ptr()).next.take() };
+        if rest.head.is_some() {
+            rest.tail = self.tail;
+            rest.len = self.len - index;
+            self.tail = Some(last);
+            self.len = index;
+        }
+        rest
+    }
+
+    /// Moves the first `n` elements to the back, keeping their order.
+    ///
+    /// Panics if `n` is greater than the length.
+    pub fn rotate_left(&mut self, n: usize) {
+        if n > self.len {
+            panic!("rotation {} is out of bounds for a list of length {}", n, self.len);
+        }
+        let mut rotated = self.split_at(n);
+        rotated.append(self);
+        *self = rotated;
+    }
+
+    /// Removes consecutive repeated elements, keeping the first of each run
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes consecutive elements that map to the same key
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes each element `same` returns true for when passed it and
+    /// the element kept before it
+    pub fn dedup_by<F>(&mut self, mut same: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut node = match self.head {
+            Some(node) => node,
+            None => return,
+        };
+        unsafe {
+            while let Some(next) = (*node.as_ptr()).next {
+                if !same(&mut (*next.as_ptr()).data, &mut (*node.as_ptr()).data) {
+                    node = next;
+                    continue;
+                }
+                (*node.as_ptr()).next = (*next.as_ptr()).next;
+                if (*node.as_ptr()).next.is_none() {
+                    self.tail = Some(node);
+                }
+                self.len -= 1;
+                drop(Box::from_raw(next.as_ptr()));
+            }
+        }
+    }
+
+    /// Splits the list into the elements `pred` returns true for and the
+    /// rest, keeping their order and relinking the existing nodes
+    pub fn partition<F>(mut self, mut pred: F) -> (Linked<T>, Linked<T>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut matching = Linked::new();
+        let mut rest = Linked::new();
+        while let Some(node) = self.pop_node() {
+            if pred(unsafe { &(*node.as_ptr()).data }) {
+                matching.push_back_node(node);
+            } else {
+                rest.push_back_node(node);
+            }
+        }
+        (matching, rest)
+    }
+
+    /// Sorts the list with a stable merge sort that relinks the nodes,
+    /// without moving or copying any element
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    /// Stable sort, ordering the elements with `compare`. If `compare`
+    /// panics, the elements are leaked rather than dropped.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (head, len) = (self.head.take(), mem::replace(&mut self.len, 0));
+        self.tail = None;
+        // the chain was unlinked from the list, so the sort owns all of it
+        self.head = unsafe { sort_links(head, len, &mut compare) };
+        self.len = len;
+        self.relink_tail();
+    }
+
+    /// Whether every element is at most the next one
+    pub fn is_sorted(&self) -> bool
+    where
+        T: Ord,
+    {
+        let mut iter = self.iter();
+        let mut prev = match iter.next() {
+            Some(data) => data,
+            None => return true,
+        };
+        for data in iter {
+            if prev > data {
+                return false;
+            }
+            prev = data;
+        }
+        true
+    }
+
+    /// Inserts `data` into a sorted list, after any equal elements, so the
+    /// list stays sorted. O(1) when `data` goes last, otherwise a walk.
+    pub fn insert_sorted(&mut self, data: T)
+    where
+        T: Ord,
+    {
+        if self.last().is_none_or(|last| *last <= data) {
+            self.push_back(data);
+            return;
+        }
+        let index = self.iter().take_while(|existing| **existing <= data).count();
+        self.insert(index, data);
+    }
+
+    /// Searches a sorted list like `slice::binary_search`: `Ok` with the
+    /// index of the first element equal to `data`, or `Err` with the index
+    /// where it would be inserted. A list can't be bisected, so this walks
+    /// from the head and stops at the first element not less than `data`.
+    pub fn position_sorted(&self, data: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        for (index, existing) in self.iter().enumerate() {
+            match existing.cmp(data) {
+                Ordering::Less => {}
+                Ordering::Equal => return Ok(index),
+                Ordering::Greater => return Err(index),
+            }
+        }
+        Err(self.len)
+    }
+
+    /// Merges two sorted lists into one sorted list. Of equal elements,
+    /// the ones from `self` come first.
+    pub fn merge(mut self, mut other: Linked<T>) -> Linked<T>
+    where
+        T: Ord,
+    {
+        let mut merged = Linked::new();
+        let (left, right) = (self.head.take(), other.head.take());
+        self.tail = None;
+        other.tail = None;
+        // both chains were unlinked from their lists
+        merged.head = unsafe { merge_links(left, right, &mut |a: &T, b: &T| a.cmp(b)) };
+        merged.len = self.len + other.len;
+        merged.relink_tail();
+        merged
+    }
+
+    /// Points the tail at the last node after the links were rewritten
+    fn relink_tail(&mut self) {
+        let mut last = None;
+        let mut node = self.head;
+        while let Some(current) = node {
+            last = node;
+            node = unsafe { (*current.as_ptr()).next };
+        }
+        self.tail = last;
+    }
+
+    /// Node `index` places after the head
+    fn node_at(&self, index: usize) -> Link<T> {
+        let mut node = self.head;
+        for _ in 0..index {
+            node = unsafe { (*node?.as_ptr()).next };
+        }
+        node
+    }
+
+    /// Removes the elements in `range`, handing them out as it goes.
+    ///
+    /// Panics if the range is decreasing or ends past the length.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        if start > end || end > self.len {
+            panic!("drain range {}..{} is out of bounds for a list of length {}", start, end, self.len);
+        }
+
+        let mut cursor = self.cursor_front_mut();
+        for _ in 0..start {
+            cursor.move_next();
+        }
+        Drain {
+            cursor,
+            remaining: end - start
+        }
+    }
+
+    /// Moves every element of `other` in after the element at `index`.
+    /// Reaching `index` walks the list, except for the last element,
+    /// where this is an O(1) `append`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn splice_after(&mut self, index: usize, mut other: Linked<T>) {
+        if index >= self.len {
+            panic!("splice index {} is out of bounds for a list of length {}", index, self.len);
+        }
+        if index == self.len - 1 {
+            return self.append(&mut other);
+        }
+        let (head, tail) = match (other.head.take(), other.tail.take()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return,
+        };
+
+        let node = self.node_at(index).unwrap();
+        unsafe {
+            (*tail.as_ptr()).next = (*node.as_ptr()).next;
+            (*node.as_ptr()).next = Some(head);
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Cursor starting on the head, or on the ghost position if empty
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            prev: None,
+            index: 0
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: unsafe { borrow_link(self.head) }
+        }
+    }
+
+    /// Pairs of elements from both lists, stopping at the end of the
+    /// shorter one
+    pub fn zip<'a, U>(&'a self, other: &'a Linked<U>) -> std::iter::Zip<Iter<'a, T>, Iter<'a, U>> {
+        self.iter().zip(other.iter())
+    }
+
+    /// Links the nodes of `other` between those of `self`, alternating
+    /// from the head of `self`, without reallocating any. Whatever is
+    /// left of the longer list follows.
+    pub fn interleave(&mut self, mut other: Linked<T>) {
+        let mut mine = self.head.take();
+        let mut theirs = other.head.take();
+        self.len += other.len;
+        other.tail = None;
+        other.len = 0;
+
+        let mut slot = &mut self.head;
+        loop {
+            let node = match mine {
+                Some(node) => node,
+                None => {
+                    *slot = theirs;
+                    break;
+                }
+            };
+            unsafe {
+                mine = (*node.as_ptr()).next;
+                *slot = Some(node);
+                slot = &mut (*node.as_ptr()).next;
+            }
+            std::mem::swap(&mut mine, &mut theirs);
+        }
+        self.relink_tail();
+    }
+
+    /// Views of `size` consecutive elements from the head, the last one
+    /// shorter if the length isn't a multiple of `size`.
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> Chunks<'_, T> {
+        if size == 0 {
+            panic!("chunk size must be positive");
+        }
+        Chunks {
+            next: unsafe { borrow_link(self.head) },
+            size
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: unsafe { borrow_link_mut(self.head) }
+        }
+    }
 }
 
 
-impl Linked {
-    pub fn new() -> Self {
-        Linked {
-            head: None
+fn new_node<T>(data: T, next: Link<T>) -> NonNull<LinkedNode<T>> {
+    let node = Box::new(LinkedNode {
+        data,
+        next
+    });
+    NonNull::from(Box::leak(node))
+}
+
+/// Node `link` points to, borrowed for `'a`.
+///
+/// # Safety
+///
+/// The node must stay alive for `'a`, without being borrowed mutably.
+unsafe fn borrow_link<'a, T>(link: Link<T>) -> Option<&'a LinkedNode<T>> {
+    link.map(|node| &*node.as_ptr())
+}
+
+/// Node `link` points to, borrowed mutably for `'a`.
+///
+/// # Safety
+///
+/// The node must stay alive for `'a`, without being borrowed otherwise.
+unsafe fn borrow_link_mut<'a, T>(link: Link<T>) -> Option<&'a mut LinkedNode<T>> {
+    link.map(|node| &mut *node.as_ptr())
+}
+
+/// Merge sorts the `len` nodes starting at `head`.
+///
+/// # Safety
+///
+/// The chain from `head` must have `len` nodes and no other owner.
+unsafe fn sort_links<T, F>(head: Link<T>, len: usize, compare: &mut F) -> Link<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if len <= 1 {
+        return head;
+    }
+
+    let mut middle = head.unwrap();
+    for _ in 1..len / 2 {
+        middle = (*middle.as_ptr()).next.unwrap();
+    }
+    let right = (*middle.as_ptr()).next.take();
+
+    let left = sort_links(head, len / 2, compare);
+    let right = sort_links(right, len - len / 2, compare);
+    merge_links(left, right, compare)
+}
+
+/// Merges two sorted chains, taking from `left` on ties to keep the merge stable.
+///
+/// # Safety
+///
+/// Both chains must have no other owner.
+unsafe fn merge_links<T, F>(mut left: Link<T>, mut right: Link<T>, compare: &mut F) -> Link<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut head = None;
+    let mut end = &mut head;
+    while let (Some(l), Some(r)) = (left, right) {
+        let source = if compare(&(*r.as_ptr()).data, &(*l.as_ptr()).data) == Ordering::Less {
+            &mut right
+        } else {
+            &mut left
+        };
+        let node = source.unwrap();
+        *source = (*node.as_ptr()).next.take();
+        *end = Some(node);
+        end = &mut (*node.as_ptr()).next;
+    }
+    *end = if left.is_some() { left } else { right };
+    head
+}
+
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Index of the current element, `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        if self.index < self.list.len {
+            Some(self.index)
+        } else {
+            None
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        let current = *self.slot();
+        current.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// Moves to the next element, from the last one to the ghost
+    /// position, and from the ghost position to the head
+    pub fn move_next(&mut self) {
+        match *self.slot() {
+            Some(node) => {
+                self.prev = Some(node);
+                self.index += 1;
+            }
+            None => {
+                self.prev = None;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Inserts `data` before the current element and stays on the
+    /// current one. On the ghost position this pushes at the back.
+    pub fn insert_before(&mut self, data: T) {
+        let slot = self.slot();
+        let node = new_node(data, *slot);
+        *slot = Some(node);
+
+        if unsafe { (*node.as_ptr()).next.is_none() } {
+            self.list.tail = Some(node);
+        }
+        self.prev = Some(node);
+        self.index += 1;
+        self.list.len += 1;
+    }
+
+    /// Inserts `data` after the current element and stays on the
+    /// current one. On the ghost position this pushes at the head.
+    pub fn insert_after(&mut self, data: T) {
+        let current = match *self.slot() {
+            Some(node) => node,
+            None => {
+                self.list.add(data);
+                self.prev = self.list.tail;
+                self.index = self.list.len;
+                return;
+            }
+        };
+
+        unsafe {
+            let node = new_node(data, (*current.as_ptr()).next);
+            (*current.as_ptr()).next = Some(node);
+            if (*node.as_ptr()).next.is_none() {
+                self.list.tail = Some(node);
+            }
         }
+        self.list.len += 1;
     }
-        
-    pub fn add(&mut self, data: i32) {
-        let mut new_node = LinkedNode::new(data);
-        if self.head.is_some() {
-            let h = self.head.clone();
-            new_node.next = Some(Box::new(h.unwrap()));
+
+    /// Unlinks the current element and returns it, moving on to the
+    /// element after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let slot = self.slot();
+        let node = (*slot)?;
+        unsafe {
+            *slot = (*node.as_ptr()).next;
+            if slot.is_none() {
+                self.list.tail = self.prev;
+            }
+            self.list.len -= 1;
+            Some(Box::from_raw(node.as_ptr()).data)
+        }
+    }
+
+    /// Moves every element after the current one into a new list. On the
+    /// ghost position the whole list is moved.
+    pub fn split_after(&mut self) -> Linked<T> {
+        let (index, len, tail) = (self.index, self.list.len, self.list.tail);
+        let current = match *self.slot() {
+            Some(node) => node,
+            None => {
+                self.prev = None;
+                self.index = 0;
+                return std::mem::take(self.list);
+            }
+        };
+
+        let mut rest = Linked::new();
+        rest.head = unsafe { (*current.as_ptr()).next.take() };
+        if rest.head.is_some() {
+            rest.tail = tail;
+            rest.len = len - index - 1;
+            self.list.tail = Some(current);
+            self.list.len = index + 1;
+        }
+        rest
+    }
+
+    /// Link to the current node, in the head or in the previous node
+    fn slot(&mut self) -> &mut Link<T> {
+        match self.prev {
+            // prev is a node of the list the cursor borrows mutably
+            Some(prev) => unsafe { &mut (*prev.as_ptr()).next },
+            None => &mut self.list.head,
+        }
+    }
+}
+
+
+impl<'a, T> Chunk<'a, T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false, `chunks` never yields an empty view
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> std::iter::Take<Iter<'a, T>> {
+        Iter { next: Some(self.head) }.take(self.len)
+    }
+}
+
+impl<'a, T> Clone for Chunk<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Chunk<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Chunk<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// The list owns its nodes, and a node the rest of the chain after it, so
+// both are as thread safe as their elements. The iterators and chunks
+// borrow nodes, and follow from these.
+unsafe impl<T: Send> Send for Linked<T> {}
+
+unsafe impl<T: Sync> Sync for Linked<T> {}
+
+unsafe impl<T: Send> Send for LinkedNode<T> {}
+
+unsafe impl<T: Sync> Sync for LinkedNode<T> {}
+
+// The cursor only borrows the list, so it is as thread safe as
+// `&mut Linked<T>`
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+/// Frees the nodes one at a time from the head
+impl<T> Drop for Linked<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T: Clone> Clone for Linked<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+/// Lists the elements like a `Vec`. Formatting the nested nodes instead
+/// would recurse once per node.
+impl<T: fmt::Debug> fmt::Debug for Linked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Linked<T> {
+    fn heap_size(&self) -> usize {
+        let nodes = self.len * mem::size_of::<LinkedNode<T>>();
+        nodes + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> Default for Linked<T> {
+    fn default() -> Self {
+        Linked::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Linked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Linked<T> {}
+
+/// Lists compare element by element, like slices
+impl<T: PartialOrd> PartialOrd for Linked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for Linked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for Linked<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for data in self.iter() {
+            data.hash(state);
+        }
+    }
+}
+
+/// Formats the list as `[1 -> 2 -> 3]`
+impl<T: fmt::Display> fmt::Display for Linked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, data) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{}", data)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T> FromIterator<T> for Linked<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Linked::new();
+        list.extend(iter);
+        list
+    }
+}
+
+/// Elements are pushed at the back, in iteration order
+impl<T> Extend<T> for Linked<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Linked<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<Linked<T>> for Vec<T> {
+    fn from(list: Linked<T>) -> Self {
+        let mut vec = Vec::with_capacity(list.len());
+        vec.extend(list);
+        vec
+    }
+}
+
+/// Walks `index` links from the head, in O(n) unlike a `Vec`.
+///
+/// Panics if `index` is out of bounds, see `get` for a checked version.
+impl<T> Index<usize> for Linked<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(data) => data,
+            None => panic!("index {} is out of bounds for a list of length {}", index, self.len),
+        }
+    }
+}
+
+/// Walks `index` links from the head, in O(n) unlike a `Vec`.
+///
+/// Panics if `index` is out of bounds, see `get_mut` for a checked version.
+impl<T> IndexMut<usize> for Linked<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(data) => data,
+            None => panic!("index {} is out of bounds for a list of length {}", index, len),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            // the iterator borrows the list, which keeps every node alive
+            self.next = unsafe { borrow_link(node.next) };
+            &node.data
+        })
+    }
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = Chunk<'a, T>;
+
+    fn next(&mut self) -> Option<Chunk<'a, T>> {
+        let head = self.next?;
+        let mut len = 0;
+        while len < self.size {
+            match self.next {
+                Some(node) => self.next = unsafe { borrow_link(node.next) },
+                None => break,
+            }
+            len += 1;
+        }
+        Some(Chunk {
+            head,
+            len
+        })
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.cursor.remove_current()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.take().map(|node| {
+            // each node is handed out once, while the list is borrowed mutably
+            self.next = unsafe { borrow_link_mut(node.next) };
+            &mut node.data
+        })
+    }
+}
+
+impl<T> IntoIterator for Linked<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Linked<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for Chunk<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::Take<Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Linked<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const DEEP: usize = if cfg!(miri) { 1_000 } else { 1_000_000 };
+
+    /// Pairs of a key and an extra value. The keys are few so most
+    /// lists have equal keys, whose order shows whether a sort was stable.
+    fn pairs() -> impl Strategy<Value = Vec<(u8, usize)>> {
+        proptest::collection::vec((0..16u8, any::<usize>()), 0..200)
+    }
+
+    /// Proptest reads and writes files for failure persistence, which
+    /// Miri's isolation forbids, and runs far fewer cases in the time
+    fn config() -> ProptestConfig {
+        if cfg!(miri) {
+            ProptestConfig { cases: 2, failure_persistence: None, ..ProptestConfig::default() }
         } else {
-            new_node.next = None;
-        } 
-        self.head = Some(new_node);
+            ProptestConfig::default()
+        }
+    }
+
+    /// Compares by the key alone, like the sorts under test must
+    #[derive(Clone,Copy,Debug,PartialEq,Eq)]
+    struct Keyed(u8, usize);
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
 
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
     }
 
-    pub fn remove(&mut self) {
-        if self.head.is_some() {
-            let h = self.head.clone();
-            self.head = h.unwrap().get_next();
+    #[test]
+    fn dropping_a_million_elements_does_not_overflow_the_stack() {
+        let mut list = Linked::new();
+        for i in 0..DEEP {
+            list.add(i);
         }
+        assert_eq!(list.len(), DEEP);
+        drop(list);
+    }
 
+    #[test]
+    fn cloning_a_deep_list_copies_every_element() {
+        let list: Linked<usize> = (0..DEEP).collect();
+        let copy = list.clone();
+        drop(list);
+        assert_eq!(copy.len(), DEEP);
+        assert_eq!(copy.last(), Some(&(DEEP - 1)));
+        assert!(copy.iter().copied().eq(0..DEEP));
     }
 
+    #[test]
+    fn debug_lists_the_elements_of_a_deep_list() {
+        let list: Linked<usize> = (0..DEEP).collect();
+        let printed = format!("{:?}", list);
+        assert!(printed.starts_with("[0, 1, 2, "));
+        assert!(printed.ends_with(&format!(", {}]", DEEP - 1)));
+        assert_eq!(format!("{:?}", Linked::<i32>::new()), "[]");
+    }
+
+    proptest! {
+        #![proptest_config(config())]
+
+        #[test]
+        fn sort_by_orders_like_a_stable_vec_sort(pairs in pairs()) {
+            let mut list = Linked::from(pairs.clone());
+            let mut expected = pairs;
+            list.sort_by(|a, b| a.0.cmp(&b.0));
+            expected.sort_by_key(|pair| pair.0);
+
+            prop_assert_eq!(list.len(), expected.len());
+            prop_assert_eq!(list.last(), expected.last());
+            prop_assert_eq!(Vec::from(list), expected);
+        }
+
+        #[test]
+        fn sort_keeps_equal_elements_in_order(pairs in pairs()) {
+            let items: Vec<Keyed> = pairs.into_iter().map(|(key, value)| Keyed(key, value)).collect();
+            let mut list = Linked::from(items.clone());
+            list.sort();
+            let mut expected = items;
+            expected.sort();
+
+            prop_assert!(list.is_sorted());
+            prop_assert!(list.iter().map(|item| (item.0, item.1)).eq(expected.iter().map(|item| (item.0, item.1))));
+        }
+
+        #[test]
+        fn merge_takes_equal_elements_from_self_first(left in pairs(), right in pairs()) {
+            let mut left: Vec<Keyed> = left.into_iter().map(|(key, value)| Keyed(key, value)).collect();
+            let mut right: Vec<Keyed> = right.into_iter().map(|(key, value)| Keyed(key, value)).collect();
+            left.sort();
+            right.sort();
+
+            let merged = Linked::from(left.clone()).merge(Linked::from(right.clone()));
+            let mut expected = left;
+            expected.extend(right);
+            expected.sort();
+
+            prop_assert_eq!(merged.len(), expected.len());
+            prop_assert_eq!(merged.last().map(|item| item.1), expected.last().map(|item| item.1));
+            prop_assert!(merged.iter().map(|item| (item.0, item.1)).eq(expected.iter().map(|item| (item.0, item.1))));
+        }
+
+        #[test]
+        fn insert_sorted_keeps_the_list_sorted_and_stable(pairs in pairs()) {
+            let mut list = Linked::new();
+            let mut expected = Vec::new();
+            for (key, value) in pairs {
+                list.insert_sorted(Keyed(key, value));
+                expected.push(Keyed(key, value));
+                prop_assert!(list.is_sorted());
+            }
+            expected.sort();
+
+            prop_assert_eq!(list.last().map(|item| item.1), expected.last().map(|item| item.1));
+            prop_assert!(list.iter().map(|item| (item.0, item.1)).eq(expected.iter().map(|item| (item.0, item.1))));
+        }
+    }
+
+    #[test]
+    fn sorting_a_long_list_relinks_the_tail() {
+        let len = if cfg!(miri) { 2_000 } else { 200_000 };
+        let mut list: Linked<u64> = (0..len as u64).map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15)).collect();
+        list.sort();
+        assert!(list.is_sorted());
+        let max = *list.iter().max().unwrap();
+        assert_eq!(list.last(), Some(&max));
+        list.push_back(max);
+        assert_eq!(list.len(), len + 1);
+    }
+
+    #[test]
+    fn nth_find_and_last_walk_without_cloning() {
+        let list: Linked<String> = (0..10).map(|i| i.to_string()).collect();
+        assert_eq!(list.nth(0).map(String::as_str), Some("0"));
+        assert_eq!(list.nth(7).map(String::as_str), Some("7"));
+        assert_eq!(list.nth(10), None);
+        assert_eq!(list.last().map(String::as_str), Some("9"));
+
+        let mut visited = 0;
+        assert_eq!(list.find(|data| { visited += 1; data.starts_with('3') }).map(String::as_str), Some("3"));
+        assert_eq!(visited, 4);
+        assert_eq!(list.find(|data| data.len() > 1), None);
+
+        let empty = Linked::<String>::new();
+        assert_eq!(empty.nth(0), None);
+        assert_eq!(empty.last(), None);
+        assert_eq!(empty.find(|_| true), None);
+    }
+
+    #[test]
+    fn insert_sorted_at_either_end_relinks_the_tail() {
+        let mut list = Linked::new();
+        for data in [5, 3, 8, 1, 9, 9, 0].iter().copied() {
+            list.insert_sorted(data);
+        }
+        assert_eq!(Vec::from(list.clone()), vec![0, 1, 3, 5, 8, 9, 9]);
+        list.push_back(10);
+        assert_eq!(list.last(), Some(&10));
+        assert_eq!(list.len(), 8);
+    }
+
+    #[test]
+    fn is_sorted_allows_equal_neighbours() {
+        assert!(Linked::<u32>::new().is_sorted());
+        assert!(Linked::from(vec![1]).is_sorted());
+        assert!(Linked::from(vec![1, 1, 2, 2]).is_sorted());
+        assert!(!Linked::from(vec![1, 2, 1]).is_sorted());
+    }
+
+    #[test]
+    fn position_sorted_matches_a_binary_search_for_the_first_equal() {
+        let list = Linked::from(vec![1, 3, 3, 3, 7, 9]);
+        let vec = Vec::from(list.clone());
+        for probe in 0..11 {
+            let expected = match vec.binary_search(&probe) {
+                Ok(_) => Ok(vec.iter().position(|&data| data == probe).unwrap()),
+                Err(index) => Err(index),
+            };
+            assert_eq!(list.position_sorted(&probe), expected, "probe {}", probe);
+        }
+        assert_eq!(Linked::<u32>::new().position_sorted(&4), Err(0));
+    }
+
+    #[test]
+    fn zip_stops_at_the_shorter_list() {
+        let numbers: Linked<u32> = (0..5).collect();
+        let letters: Linked<char> = "abc".chars().collect();
+        let pairs: Vec<(&u32, &char)> = numbers.zip(&letters).collect();
+        assert_eq!(pairs, vec![(&0, &'a'), (&1, &'b'), (&2, &'c')]);
+        assert_eq!(letters.zip(&Linked::<u32>::new()).count(), 0);
+    }
+
+    #[test]
+    fn interleave_alternates_and_appends_the_rest() {
+        for &(mine, theirs) in [(0, 0), (0, 3), (3, 0), (4, 4), (2, 5), (5, 2)].iter() {
+            let mut list: Linked<u32> = (0..mine).map(|i| i * 2).collect();
+            let other: Linked<u32> = (0..theirs).map(|i| i * 2 + 1).collect();
+            list.interleave(other);
+
+            let shorter = mine.min(theirs);
+            let mut expected: Vec<u32> = (0..shorter * 2).collect();
+            expected.extend((shorter..mine).map(|i| i * 2));
+            expected.extend((shorter..theirs).map(|i| i * 2 + 1));
+            assert_eq!(Vec::from(list.clone()), expected);
+            assert_eq!(list.len(), expected.len());
+            assert_eq!(list.last(), expected.last());
+
+            list.push_back(100);
+            assert_eq!(list.last(), Some(&100));
+        }
+    }
+
+    #[test]
+    fn chunks_match_slice_chunks() {
+        for len in 0..12 {
+            let list: Linked<usize> = (0..len).collect();
+            let vec: Vec<usize> = (0..len).collect();
+            for size in 1..6 {
+                let chunks: Vec<Vec<usize>> = list.chunks(size).map(|chunk| chunk.iter().copied().collect()).collect();
+                let expected: Vec<Vec<usize>> = vec.chunks(size).map(|chunk| chunk.to_vec()).collect();
+                assert_eq!(chunks, expected, "len {} size {}", len, size);
+                assert!(list.chunks(size).all(|chunk| !chunk.is_empty() && chunk.len() <= size));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be positive")]
+    fn chunks_of_zero_panic() {
+        Linked::from(vec![1]).chunks(0);
+    }
+
+    #[test]
+    fn indexing_reads_and_writes_in_place() {
+        let mut list: Linked<u32> = (0..5).collect();
+        assert_eq!(list[0], 0);
+        assert_eq!(list[4], 4);
+        list[2] = 20;
+        list[4] += 40;
+        assert_eq!(Vec::from(list.clone()), vec![0, 1, 20, 3, 44]);
+        assert_eq!(list.last(), Some(&44));
+        assert_eq!(list.get(5), None);
+        assert_eq!(list.get_mut(5), None);
+
+        *list.first_mut().unwrap() = 7;
+        *list.last_mut().unwrap() = 9;
+        assert_eq!((list.first(), list.last()), (Some(&7), Some(&9)));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 is out of bounds for a list of length 3")]
+    fn indexing_past_the_end_panics() {
+        let list = Linked::from(vec![1, 2, 3]);
+        let _ = list[3];
+    }
+
+    #[test]
+    #[should_panic(expected = "index 0 is out of bounds for a list of length 0")]
+    fn mutably_indexing_an_empty_list_panics() {
+        let mut list = Linked::<u32>::new();
+        list[0] = 1;
+    }
 }
@@ -0,0 +1,249 @@
+//! Least recently used cache over `HashTable` and `DoublyLinked`
+
+use std::fmt;
+use std::hash::Hash;
+
+use crate::doubly::NodeHandle;
+use crate::{DoublyLinked, HashTable};
+
+/// Called with every entry the cache evicts to make room
+type EvictionCallback<K, V> = Box<dyn FnMut(K, V) + Send>;
+
+
+/// Map holding at most `capacity` entries. The table finds an entry's
+/// node in the list, which is kept from least to most recently used, so
+/// lookups, promotions and evictions are all O(1). Keys are stored in
+/// both, hence `K: Clone`.
+pub struct LruCache<K, V> {
+    table: HashTable<K, NodeHandle<(K, V)>>,
+    list: DoublyLinked<(K, V)>,
+    capacity: usize,
+    on_evict: Option<EvictionCallback<K, V>>
+}
+
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            panic!("cache capacity must be positive");
+        }
+        LruCache {
+            table: HashTable::new(),
+            list: DoublyLinked::new(),
+            capacity,
+            on_evict: None
+        }
+    }
+
+    /// Cache handing every evicted entry to `on_evict`. Entries taken
+    /// out with `remove`, `pop_lru` or `clear` aren't evictions.
+    pub fn with_eviction_callback<F>(capacity: usize, on_evict: F) -> Self
+    where
+        F: FnMut(K, V) + Send + 'static,
+    {
+        let mut cache = LruCache::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Inserts `value` under `key` as the most recently used entry,
+    /// returning the value it replaced. A new key in a full cache evicts
+    /// the least recently used entry.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&handle) = self.table.get(&key) {
+            unsafe {
+                self.list.move_to_back(handle);
+                return Some(std::mem::replace(&mut self.list.get_mut(handle).1, value));
+            }
+        }
+
+        if self.list.len() == self.capacity {
+            if let Some((key, value)) = self.pop_lru() {
+                if let Some(on_evict) = self.on_evict.as_mut() {
+                    on_evict(key, value);
+                }
+            }
+        }
+        let handle = self.list.push_back((key.clone(), value));
+        self.table.insert(key, handle);
+        None
+    }
+
+    /// Value of `key`, which becomes the most recently used entry
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handle = *self.table.get(key)?;
+        unsafe {
+            self.list.move_to_back(handle);
+            Some(&mut self.list.get_mut(handle).1)
+        }
+    }
+
+    /// Value of `key`, leaving the usage order alone
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let handle = *self.table.get(key)?;
+        unsafe { Some(&self.list.get(handle).1) }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.table.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let handle = self.table.remove(key)?;
+        unsafe { Some(self.list.remove(handle).1) }
+    }
+
+    /// Removes and returns the least recently used entry
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_front()?;
+        self.table.remove(&key);
+        Some((key, value))
+    }
+
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.list.clear();
+    }
+
+    /// Entries from the most to the least recently used
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.list.iter().rev().map(|(key, value)| (key, value))
+    }
+}
+
+
+// The eviction callback is only `Send`, but it is only run through
+// `&mut self`, so sharing the cache never shares the callback
+unsafe impl<K: Sync, V: Sync> Sync for LruCache<K, V> {}
+
+impl<K: Hash + Eq + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn keys<V>(cache: &LruCache<u32, V>) -> Vec<u32> {
+        cache.iter().map(|(&key, _)| key).collect()
+    }
+
+    #[test]
+    fn the_least_recently_used_entry_is_evicted_first() {
+        let mut cache = LruCache::new(3);
+        for key in 1..=3 {
+            cache.insert(key, key * 10);
+        }
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        // reading and replacing promote, peeking doesn't
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.peek(&2), Some(&20));
+        assert_eq!(keys(&cache), [1, 3, 2]);
+        assert_eq!(cache.insert(3, 33), Some(30));
+        assert_eq!(keys(&cache), [3, 1, 2]);
+
+        cache.insert(4, 40);
+        assert!(!cache.contains_key(&2));
+        cache.insert(5, 50);
+        assert!(!cache.contains_key(&1));
+        assert_eq!(keys(&cache), [5, 4, 3]);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn the_callback_sees_evictions_only() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = evicted.clone();
+        let mut cache = LruCache::with_eviction_callback(2, move |key, value| sink.lock().unwrap().push((key, value)));
+
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        cache.insert(1, "aa");
+        cache.insert(3, "c");
+        cache.insert(4, "d");
+        assert_eq!(*evicted.lock().unwrap(), [(2, "b"), (1, "aa")]);
+
+        assert_eq!(cache.remove(&3), Some("c"));
+        assert_eq!(cache.pop_lru(), Some((4, "d")));
+        cache.insert(5, "e");
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(evicted.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn random_use_matches_a_vec_model() {
+        let mut state = 0x2545_f491_u64;
+        let mut cache = LruCache::new(8);
+        // least recently used first
+        let mut model: Vec<(u32, u32)> = Vec::new();
+        let steps = if cfg!(miri) { 500 } else { 5_000 };
+        for step in 0..steps {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let key = (state % 16) as u32;
+            let position = model.iter().position(|&(k, _)| k == key);
+            match state >> 60 {
+                0..=7 => {
+                    let old = position.map(|i| model.remove(i).1);
+                    if old.is_none() && model.len() == 8 {
+                        model.remove(0);
+                    }
+                    model.push((key, step));
+                    assert_eq!(cache.insert(key, step), old);
+                }
+                8..=13 => {
+                    let expected = position.map(|i| {
+                        let entry = model.remove(i);
+                        model.push(entry);
+                        entry.1
+                    });
+                    assert_eq!(cache.get(&key).copied(), expected);
+                }
+                _ => assert_eq!(cache.remove(&key), position.map(|i| model.remove(i).1)),
+            }
+            let expected: Vec<_> = model.iter().rev().map(|(k, v)| (k, v)).collect();
+            assert_eq!(cache.iter().collect::<Vec<_>>(), expected, "after step {}", step);
+        }
+    }
+
+    #[test]
+    fn a_single_entry_cache_keeps_the_latest_key() {
+        let mut cache = LruCache::new(1);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.peek(&"a"), None);
+        assert_eq!(cache.peek(&"b"), Some(&2));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "cache capacity must be positive")]
+    fn a_zero_capacity_panics() {
+        LruCache::<u32, u32>::new(0);
+    }
+}
@@ -0,0 +1,93 @@
+//! Estimates of how much memory values hold, to follow the growth of
+//! long lived state
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::mem;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Value that can estimate the heap memory it owns, all the way down
+pub trait MemoryFootprint {
+    /// Bytes allocated for the value and everything it owns, not counting
+    /// the value itself. Spare capacity counts, allocator overhead doesn't.
+    fn heap_size(&self) -> usize;
+
+    /// Bytes of the value itself plus its heap size
+    fn memory_footprint(&self) -> usize {
+        mem::size_of_val(self) + self.heap_size()
+    }
+}
+
+
+macro_rules! inline_only {
+    ($($ty:ty),*) => {
+        $(
+            impl MemoryFootprint for $ty {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+inline_only!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+inline_only!(f32, f64, bool, char, (), Duration, Instant, SystemTime);
+
+impl MemoryFootprint for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: MemoryFootprint + ?Sized> MemoryFootprint for Box<T> {
+    fn heap_size(&self) -> usize {
+        (**self).memory_footprint()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<A: MemoryFootprint, B: MemoryFootprint> MemoryFootprint for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for [T] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self[..].heap_size()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+/// Counts a slot and a control byte per entry of capacity, as the std
+/// table lays them out
+impl<K: MemoryFootprint, V: MemoryFootprint, S> MemoryFootprint for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        let entries: usize = self.iter().map(|(key, value)| key.heap_size() + value.heap_size()).sum();
+        self.capacity() * (mem::size_of::<(K, V)>() + 1) + entries
+    }
+}
+
+/// Counts the entries only, the spare room in the tree's nodes is unknown
+impl<K: MemoryFootprint, V: MemoryFootprint> MemoryFootprint for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        let entries: usize = self.iter().map(|(key, value)| key.heap_size() + value.heap_size()).sum();
+        self.len() * (mem::size_of::<K>() + mem::size_of::<V>()) + entries
+    }
+}
@@ -0,0 +1,281 @@
+//! Merkle tree with inclusion proofs, generic over the hash function
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::Hasher as _;
+
+use crate::memory::MemoryFootprint;
+
+/// Hash function of a `MerkleTree`. Leaves and inner nodes should hash
+/// differently, e.g. behind a distinct prefix byte, so an inner node
+/// can't be passed off as a leaf.
+pub trait Hasher {
+    type Digest: Clone + PartialEq + fmt::Debug;
+
+    fn hash_leaf(data: &[u8]) -> Self::Digest;
+
+    fn hash_nodes(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+/// `Hasher` over std's `DefaultHasher`. It is not collision resistant,
+/// so it only suits tests and examples; real commitments need a
+/// cryptographic hash.
+#[derive(Clone,Copy,Debug)]
+pub struct StdHasher;
+
+
+/// Binary hash tree over a list of leaves. A node without a sibling at
+/// the end of a level moves up unchanged instead of being paired with
+/// a copy of itself.
+pub struct MerkleTree<H: Hasher> {
+    /// Leaf digests first, the root alone on the last level
+    levels: Vec<Vec<H::Digest>>
+}
+
+/// Sibling digests on the path from a leaf to the root
+#[derive(Clone,Debug,PartialEq)]
+pub struct MerkleProof<D> {
+    index: usize,
+    siblings: Vec<Sibling<D>>
+}
+
+/// Digest to hash with on the way up, and which side it goes on
+#[derive(Clone,Debug,PartialEq)]
+pub enum Sibling<D> {
+    Left(D),
+    Right(D),
+}
+
+
+impl<H: Hasher> MerkleTree<H> {
+    /// Tree over `leaves` in order. Fails when there are none.
+    pub fn from_leaves<I, L>(leaves: I) -> Result<Self, &'static str>
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<[u8]>,
+    {
+        let leaves: Vec<H::Digest> = leaves.into_iter().map(|leaf| H::hash_leaf(leaf.as_ref())).collect();
+        if leaves.is_empty() {
+            return Err("Merkle tree needs at least one leaf");
+        }
+
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => H::hash_nodes(left, right),
+                    [lone] => lone.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Ok(MerkleTree {
+            levels
+        })
+    }
+
+    pub fn root(&self) -> &H::Digest {
+        &self.levels[self.levels.len() - 1][0]
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Levels from the leaves to the root, both included
+    pub fn height(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Digest of leaf `index`
+    pub fn leaf(&self, index: usize) -> Option<&H::Digest> {
+        self.levels[0].get(index)
+    }
+
+    /// Proof that leaf `index` is in the tree, `None` past the last leaf
+    pub fn proof(&self, index: usize) -> Option<MerkleProof<H::Digest>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                siblings.push(if sibling < position {
+                    Sibling::Left(level[sibling].clone())
+                } else {
+                    Sibling::Right(level[sibling].clone())
+                });
+            }
+            position /= 2;
+        }
+        Some(MerkleProof {
+            index,
+            siblings
+        })
+    }
+
+    /// Whether `proof` ties `leaf` to a tree with root `root`
+    pub fn verify(root: &H::Digest, leaf: &[u8], proof: &MerkleProof<H::Digest>) -> bool {
+        let mut digest = H::hash_leaf(leaf);
+        for sibling in proof.siblings.iter() {
+            digest = match sibling {
+                Sibling::Left(left) => H::hash_nodes(left, &digest),
+                Sibling::Right(right) => H::hash_nodes(&digest, right),
+            };
+        }
+        digest == *root
+    }
+}
+
+impl<D> MerkleProof<D> {
+    /// Index of the leaf the proof is for
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn siblings(&self) -> &[Sibling<D>] {
+        &self.siblings
+    }
+}
+
+
+impl<H: Hasher> Clone for MerkleTree<H> {
+    fn clone(&self) -> Self {
+        MerkleTree {
+            levels: self.levels.clone()
+        }
+    }
+}
+
+impl<H: Hasher> fmt::Debug for MerkleTree<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("root", self.root())
+            .field("leaves", &self.leaf_count())
+            .finish()
+    }
+}
+
+impl<H: Hasher> MemoryFootprint for MerkleTree<H>
+where
+    H::Digest: MemoryFootprint,
+{
+    fn heap_size(&self) -> usize {
+        self.levels.heap_size()
+    }
+}
+
+impl Hasher for StdHasher {
+    type Digest = u64;
+
+    fn hash_leaf(data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u8(0);
+        hasher.write(data);
+        hasher.finish()
+    }
+
+    fn hash_nodes(left: &u64, right: &u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u8(1);
+        hasher.write_u64(*left);
+        hasher.write_u64(*right);
+        hasher.finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spells out the tree, e.g. `((a,b),c)`, so tests can check its shape
+    struct Spelled;
+
+    impl Hasher for Spelled {
+        type Digest = String;
+
+        fn hash_leaf(data: &[u8]) -> String {
+            String::from_utf8(data.to_vec()).unwrap()
+        }
+
+        fn hash_nodes(left: &String, right: &String) -> String {
+            format!("({},{})", left, right)
+        }
+    }
+
+    fn leaves(count: usize) -> Vec<String> {
+        (0..count).map(|i| ((b'a' + i as u8) as char).to_string()).collect()
+    }
+
+    #[test]
+    fn a_lone_node_moves_up_unpaired() {
+        let root = |count| MerkleTree::<Spelled>::from_leaves(leaves(count)).unwrap().root().clone();
+        assert_eq!(root(1), "a");
+        assert_eq!(root(3), "((a,b),c)");
+        assert_eq!(root(5), "(((a,b),(c,d)),e)");
+        assert_eq!(root(6), "(((a,b),(c,d)),(e,f))");
+        assert_eq!(root(7), "(((a,b),(c,d)),((e,f),g))");
+    }
+
+    #[test]
+    fn every_leaf_of_odd_and_even_trees_has_a_valid_proof() {
+        for count in 1..=20 {
+            let data = leaves(count);
+            let tree = MerkleTree::<StdHasher>::from_leaves(&data).unwrap();
+            assert_eq!(tree.leaf_count(), count);
+            for (index, leaf) in data.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.index(), index);
+                assert!(proof.siblings().len() < tree.height());
+                assert!(MerkleTree::<StdHasher>::verify(tree.root(), leaf.as_bytes(), &proof), "leaf {} of {}", index, count);
+            }
+            assert_eq!(tree.proof(count), None);
+        }
+    }
+
+    #[test]
+    fn the_last_leaf_of_an_odd_tree_skips_missing_siblings() {
+        let tree = MerkleTree::<Spelled>::from_leaves(leaves(5)).unwrap();
+        let proof = tree.proof(4).unwrap();
+        assert_eq!(proof.siblings(), [Sibling::Left("((a,b),(c,d))".to_string())]);
+        assert!(MerkleTree::<Spelled>::verify(tree.root(), b"e", &proof));
+
+        let proof = tree.proof(2).unwrap();
+        assert_eq!(proof.siblings(), [
+            Sibling::Right("d".to_string()),
+            Sibling::Left("(a,b)".to_string()),
+            Sibling::Right("e".to_string())
+        ]);
+    }
+
+    #[test]
+    fn a_proof_fails_for_other_data_or_a_changed_sibling() {
+        for count in [2, 3, 7, 9].iter().copied() {
+            let data = leaves(count);
+            let tree = MerkleTree::<StdHasher>::from_leaves(&data).unwrap();
+            for index in 0..count {
+                let proof = tree.proof(index).unwrap();
+                let other = &data[(index + 1) % count];
+                assert!(!MerkleTree::<StdHasher>::verify(tree.root(), other.as_bytes(), &proof));
+
+                let mut tampered = proof.clone();
+                tampered.siblings[0] = match &proof.siblings[0] {
+                    Sibling::Left(digest) => Sibling::Left(digest ^ 1),
+                    Sibling::Right(digest) => Sibling::Right(digest ^ 1),
+                };
+                assert!(!MerkleTree::<StdHasher>::verify(tree.root(), data[index].as_bytes(), &tampered));
+            }
+        }
+    }
+
+    #[test]
+    fn an_empty_tree_is_rejected() {
+        assert!(MerkleTree::<StdHasher>::from_leaves(Vec::<Vec<u8>>::new()).is_err());
+    }
+}
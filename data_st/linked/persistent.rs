@@ -0,0 +1,225 @@
+//! Immutable list whose versions share their common tails
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+struct PersistentNode<T> {
+    data: T,
+    next: Option<Arc<PersistentNode<T>>>
+}
+
+
+/// Immutable singly linked list. `cons` and `tail` return new lists in
+/// O(1) that share nodes with the original, which is never modified, so
+/// every older version stays valid as a snapshot.
+pub struct PersistentList<T> {
+    head: Option<Arc<PersistentNode<T>>>,
+    len: usize
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a PersistentNode<T>>,
+    remaining: usize
+}
+
+
+impl<T> PersistentList<T> {
+    pub fn new() -> Self {
+        PersistentList {
+            head: None,
+            len: 0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// New list with `data` in front of this one
+    pub fn cons(&self, data: T) -> Self {
+        PersistentList {
+            head: Some(Arc::new(PersistentNode {
+                data,
+                next: self.head.clone()
+            })),
+            len: self.len + 1
+        }
+    }
+
+    /// Same as `cons`
+    pub fn push_front(&self, data: T) -> Self {
+        self.cons(data)
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.data)
+    }
+
+    /// List without the head element, empty if this one is
+    pub fn tail(&self) -> Self {
+        match &self.head {
+            Some(node) => PersistentList {
+                head: node.next.clone(),
+                len: self.len - 1
+            },
+            None => PersistentList::new(),
+        }
+    }
+
+    /// Whether both lists start at the very same node, i.e. one was
+    /// cloned from the other
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            remaining: self.len
+        }
+    }
+}
+
+
+/// Frees the nodes no other version shares one at a time, stopping at
+/// the first shared one, instead of recursing down the chain. Of two
+/// versions dropped at once on different threads, `Arc::into_inner`
+/// lets exactly one take each shared node and carry on.
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            next = Arc::into_inner(node).and_then(|mut node| node.next.take());
+        }
+    }
+}
+
+/// Shares the whole list in O(1)
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+            len: self.len
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        PersistentList::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+/// The first element of the iterator becomes the head
+impl<T> FromIterator<T> for PersistentList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items.into_iter().rev().fold(PersistentList::new(), |list, data| list.cons(data))
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            self.remaining -= 1;
+            &node.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+    use std::thread;
+
+    /// Counts its drops in a shared counter
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn versions_dropped_at_once_free_the_shared_tail_once() {
+        const SHARED: usize = 100_000;
+        const VERSIONS: usize = 8;
+
+        for _ in 0..10 {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let shared: PersistentList<Tracked> = (0..SHARED).map(|_| Tracked(drops.clone())).collect();
+            let versions: Vec<PersistentList<Tracked>> = (0..VERSIONS).map(|_| shared.push_front(Tracked(drops.clone()))).collect();
+            drop(shared);
+
+            let barrier = Arc::new(Barrier::new(VERSIONS));
+            let handles: Vec<_> = versions
+                .into_iter()
+                .map(|version| {
+                    let barrier = barrier.clone();
+                    thread::spawn(move || {
+                        barrier.wait();
+                        drop(version);
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+            assert_eq!(drops.load(Ordering::Relaxed), SHARED + VERSIONS);
+        }
+    }
+
+    #[test]
+    fn dropping_a_version_keeps_the_nodes_others_share() {
+        let list: PersistentList<u32> = (0..5).collect();
+        let longer = list.push_front(9);
+        drop(list);
+        assert_eq!(longer.len(), 6);
+        assert!(longer.iter().copied().eq([9, 0, 1, 2, 3, 4].iter().copied()));
+        assert!(longer.tail().iter().copied().eq(0..5));
+    }
+}
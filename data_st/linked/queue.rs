@@ -0,0 +1,57 @@
+//! First in, first out queue over `Linked`
+
+use crate::{Iter, Linked};
+
+/// Elements come out in the order they were enqueued
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Queue<T> {
+    list: Linked<T>
+}
+
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            list: Linked::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Adds `data` at the back in O(1)
+    pub fn enqueue(&mut self, data: T) {
+        self.list.push_back(data);
+    }
+
+    /// Removes the front element in O(1)
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+
+    /// Element the next `dequeue` returns
+    pub fn front(&self) -> Option<&T> {
+        self.list.peek()
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.list.peek_mut()
+    }
+
+    /// Iterates from the front to the back
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+}
+
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Queue::new()
+    }
+}
@@ -0,0 +1,365 @@
+//! Trees answering range queries over an array: Fenwick and segment trees
+
+use std::fmt;
+use std::ops::{Add, Bound, RangeBounds, Sub};
+
+/// Array with point updates and prefix sums, both in O(log n), stored in
+/// a binary indexed tree
+#[derive(Clone)]
+pub struct FenwickTree<T> {
+    /// One based: slot `i` sums the `i & -i` elements ending at `i`
+    tree: Vec<T>
+}
+
+/// Array combining any range of its elements with an associative `op` in
+/// O(log n). The tree is padded to a power of two with `identity`, so
+/// `op` needn't be commutative.
+#[derive(Clone)]
+pub struct SegmentTree<T, F> {
+    /// Root at 1, the children of `i` at `2i` and `2i + 1`, leaves from `size`
+    tree: Vec<T>,
+
+    /// Leaves, a power of two
+    size: usize,
+
+    len: usize,
+    identity: T,
+    op: F
+}
+
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    /// `len` elements, all `T::default()`
+    pub fn new(len: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::default(); len + 1]
+        }
+    }
+
+    /// Tree over `values`, built in O(n)
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut tree = vec![T::default(); values.len() + 1];
+        tree[1..].copy_from_slice(values);
+        for i in 1..tree.len() {
+            let parent = i + lowest_bit(i);
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[i];
+            }
+        }
+        FenwickTree {
+            tree
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `delta` to element `index`. Panics if `index` is out of bounds.
+    pub fn add(&mut self, index: usize, delta: T) {
+        if index >= self.len() {
+            panic!("index {} is out of bounds for a tree of length {}", index, self.len());
+        }
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i] + delta;
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Replaces element `index` with `value`
+    pub fn set(&mut self, index: usize, value: T) {
+        let old = self.get(index);
+        self.add(index, value - old);
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        self.sum(index..=index)
+    }
+
+    /// Sum of the first `end` elements. Panics if `end` is past the length.
+    pub fn prefix_sum(&self, end: usize) -> T {
+        if end > self.len() {
+            panic!("prefix {} is out of bounds for a tree of length {}", end, self.len());
+        }
+        let mut sum = T::default();
+        let mut i = end;
+        while i > 0 {
+            sum = sum + self.tree[i];
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    /// Sum of the elements in `range`. Panics if it is out of bounds.
+    pub fn sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (start, end) = bounds(&range, self.len());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+}
+
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SegmentTree<T, F> {
+    /// Tree over `values`, where `identity` combined with any `x` on
+    /// either side gives `x`
+    pub fn new(values: Vec<T>, identity: T, op: F) -> Self {
+        let len = values.len();
+        let size = len.next_power_of_two();
+        let mut tree = vec![identity.clone(); 2 * size];
+        for (slot, value) in tree[size..].iter_mut().zip(values) {
+            *slot = value;
+        }
+        for i in (1..size).rev() {
+            tree[i] = op(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        SegmentTree {
+            tree,
+            size,
+            len,
+            identity,
+            op
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.tree[self.size + index])
+    }
+
+    /// Replaces element `index`. Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: T) {
+        self.update(index..=index, |slot| *slot = value.clone());
+    }
+
+    /// Runs `f` on every element in `range`, then recomputes their
+    /// ancestors, in O(k + log n) for k elements. Panics if the range is
+    /// out of bounds.
+    pub fn update<R, U>(&mut self, range: R, mut f: U)
+    where
+        R: RangeBounds<usize>,
+        U: FnMut(&mut T),
+    {
+        let (start, end) = bounds(&range, self.len);
+        if start == end {
+            return;
+        }
+        self.tree[self.size + start..self.size + end].iter_mut().for_each(&mut f);
+
+        let (mut low, mut high) = (self.size + start, self.size + end - 1);
+        while low > 1 {
+            low /= 2;
+            high /= 2;
+            for i in low..=high {
+                self.tree[i] = (self.op)(&self.tree[2 * i], &self.tree[2 * i + 1]);
+            }
+        }
+    }
+
+    /// Elements in `range` combined from left to right, the identity for
+    /// an empty range. Panics if the range is out of bounds.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (start, end) = bounds(&range, self.len);
+        let mut left = self.identity.clone();
+        let mut right = self.identity.clone();
+        let (mut low, mut high) = (self.size + start, self.size + end);
+        while low < high {
+            if low % 2 == 1 {
+                left = (self.op)(&left, &self.tree[low]);
+                low += 1;
+            }
+            if high % 2 == 1 {
+                high -= 1;
+                right = (self.op)(&self.tree[high], &right);
+            }
+            low /= 2;
+            high /= 2;
+        }
+        (self.op)(&left, &right)
+    }
+}
+
+
+impl<T> fmt::Debug for FenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries((0..self.len()).map(|i| self.get(i))).finish()
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for SegmentTree<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.tree[self.size..self.size + self.len].iter()).finish()
+    }
+}
+
+
+/// Lowest set bit of `i`
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+/// `range` as `start..end`, panicking if it doesn't fit in `len` elements
+fn bounds<R: RangeBounds<usize>>(range: &R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end || end > len {
+        panic!("range {}..{} is out of bounds for a tree of length {}", start, end, len);
+    }
+    (start, end)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lengths around powers of two, where the padding starts and stops
+    const LENGTHS: [usize; 7] = [0, 1, 2, 5, 8, 13, 33];
+
+    fn values(seed: u64, len: usize) -> Vec<i64> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state % 201) as i64 - 100
+            })
+            .collect()
+    }
+
+    /// Concatenation tells `ab` from `ba`, so it catches operands combined out of order
+    fn concat() -> impl Fn(&String, &String) -> String {
+        |a, b| format!("{}{}", a, b)
+    }
+
+    fn letters(len: usize) -> Vec<String> {
+        (0..len).map(|i| ((b'a' + (i % 26) as u8) as char).to_string()).collect()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn fenwick_sums_every_range_like_a_slice() {
+        for &len in LENGTHS.iter() {
+            let mut model = values(len as u64, len);
+            let mut tree = FenwickTree::from_slice(&model);
+            assert_eq!(tree.len(), len);
+            for (step, delta) in values(7, len).into_iter().enumerate() {
+                for start in 0..=len {
+                    for end in start..=len {
+                        assert_eq!(tree.sum(start..end), model[start..end].iter().sum::<i64>(), "{}..{} of {}", start, end, len);
+                    }
+                }
+                let index = step * 7 % len;
+                tree.add(index, delta);
+                model[index] += delta;
+            }
+            assert_eq!(tree.sum(..), model.iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn fenwick_new_and_set_agree_with_from_slice() {
+        for &len in LENGTHS.iter() {
+            let model = values(3, len);
+            let mut tree = FenwickTree::new(len);
+            for (i, &value) in model.iter().enumerate() {
+                tree.set(i, value);
+            }
+            assert_eq!((0..len).map(|i| tree.get(i)).collect::<Vec<_>>(), model);
+            let built = FenwickTree::from_slice(&model);
+            assert!((0..=len).all(|end| tree.prefix_sum(end) == built.prefix_sum(end)));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn segment_queries_fold_every_range_in_order() {
+        for &len in LENGTHS.iter() {
+            let mut model = letters(len);
+            let mut tree = SegmentTree::new(model.clone(), String::new(), concat());
+            assert_eq!(tree.len(), len);
+            for step in 0..len.min(10) {
+                for start in 0..=len {
+                    for end in start..=len {
+                        assert_eq!(tree.query(start..end), model[start..end].concat(), "{}..{} of {}", start, end, len);
+                    }
+                }
+                let start = step * 5 % len;
+                let end = (start + step + 1).min(len);
+                tree.update(start..end, |slot| slot.push('!'));
+                model[start..end].iter_mut().for_each(|slot| slot.push('!'));
+                tree.set(len - 1 - step, step.to_string());
+                model[len - 1 - step] = step.to_string();
+            }
+            assert_eq!(tree.query(..), model.concat());
+            assert!((0..len).all(|i| tree.get(i) == Some(&model[i])));
+            assert_eq!(tree.get(len), None);
+        }
+    }
+
+    #[test]
+    fn segment_tree_matches_a_slice_with_a_min_op() {
+        for &len in LENGTHS.iter() {
+            let mut model = values(11, len);
+            let mut tree = SegmentTree::new(model.clone(), i64::MAX, |a: &i64, b: &i64| *a.min(b));
+            for (step, value) in values(5, len).into_iter().enumerate() {
+                let index = step * 3 % len;
+                tree.set(index, value);
+                model[index] = value;
+                for start in 0..len {
+                    let end = (start + step + 1).min(len);
+                    assert_eq!(tree.query(start..end), *model[start..end].iter().min().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_trees_answer_empty_ranges() {
+        let fenwick = FenwickTree::<i64>::from_slice(&[]);
+        assert!(fenwick.is_empty());
+        assert_eq!(fenwick.sum(..), 0);
+        assert_eq!(fenwick.prefix_sum(0), 0);
+
+        let mut segment = SegmentTree::new(Vec::new(), String::new(), concat());
+        assert!(segment.is_empty());
+        assert_eq!(segment.query(..), "");
+        assert_eq!(segment.get(0), None);
+        segment.update(.., |slot| slot.push('!'));
+        assert_eq!(segment.query(0..0), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "range 2..6 is out of bounds for a tree of length 5")]
+    fn a_range_past_the_end_panics() {
+        FenwickTree::from_slice(&[1, 2, 3, 4, 5]).sum(2..6);
+    }
+}
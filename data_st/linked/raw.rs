@@ -0,0 +1,358 @@
+//! Singly linked list built only from raw pointers, enabled with the `raw`
+//! feature to compare against the `Box` based `Linked`.
+//!
+//! # Safety argument
+//!
+//! Every node is allocated with `Box::new` and turned into a `NonNull` with
+//! `Box::leak`, and is freed exactly once, with `Box::from_raw`, when
+//! `pop_front` unlinks it. No `Box` or reference to a node is kept between
+//! calls, so the pointers `head`, `tail` and the `next` links never alias
+//! a unique owner. Borrows handed out by `peek`, `iter` and friends are tied
+//! to a borrow of the list, so no node can be freed while they live.
+//! `tail` is always the last node reachable from `head`, or `None` along
+//! with `head`.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<RawNode<T>>>;
+
+struct RawNode<T> {
+    data: T,
+    next: Link<T>
+}
+
+
+pub struct RawLinked<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<RawNode<T>>>
+}
+
+/// Owning iterator, from the head to the last element
+pub struct IntoIter<T>(RawLinked<T>);
+
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    marker: PhantomData<&'a RawNode<T>>
+}
+
+pub struct IterMut<'a, T> {
+    next: Link<T>,
+    marker: PhantomData<&'a mut RawNode<T>>
+}
+
+
+impl<T> RawLinked<T> {
+    pub fn new() -> Self {
+        RawLinked {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let node = new_node(data, self.head);
+        if self.tail.is_none() {
+            self.tail = Some(node);
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, data: T) {
+        let node = new_node(data, None);
+        match self.tail {
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head| unsafe {
+            let node = Box::from_raw(head.as_ptr());
+            self.head = node.next;
+            if self.head.is_none() {
+                self.tail = None;
+            }
+            self.len -= 1;
+            node.data
+        })
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.map(|head| unsafe { &(*head.as_ptr()).data })
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| unsafe { &mut (*head.as_ptr()).data })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|tail| unsafe { &(*tail.as_ptr()).data })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|tail| unsafe { &mut (*tail.as_ptr()).data })
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            marker: PhantomData
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head,
+            marker: PhantomData
+        }
+    }
+}
+
+
+fn new_node<T>(data: T, next: Link<T>) -> NonNull<RawNode<T>> {
+    NonNull::from(Box::leak(Box::new(RawNode {
+        data,
+        next
+    })))
+}
+
+
+// The list owns its nodes, so it is as thread safe as its elements
+unsafe impl<T: Send> Send for RawLinked<T> {}
+
+unsafe impl<T: Sync> Sync for RawLinked<T> {}
+
+// The iterators borrow the list, so they are as thread safe as the
+// references to the elements they hand out
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> Drop for RawLinked<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for RawLinked<T> {
+    fn default() -> Self {
+        RawLinked::new()
+    }
+}
+
+impl<T: Clone> Clone for RawLinked<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RawLinked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for RawLinked<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = RawLinked::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for RawLinked<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.next = node.next;
+            &node.data
+        })
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.next.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.next = node.next;
+            &mut node.data
+        })
+    }
+}
+
+impl<T> IntoIterator for RawLinked<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RawLinked<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut RawLinked<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+
+// Small enough to run under Miri: `cargo +nightly miri test --features raw`
+#[cfg(all(test, feature = "raw"))]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// Counts its drops in a shared counter
+    struct Tracked(Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn assert_matches(list: &RawLinked<u32>, model: &VecDeque<u32>) {
+        assert_eq!(list.len(), model.len());
+        assert_eq!(list.is_empty(), model.is_empty());
+        assert_eq!(list.peek(), model.front());
+        assert_eq!(list.back(), model.back());
+        assert!(list.iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn interleaved_pushes_and_pops_match_a_vec_deque() {
+        let mut list = RawLinked::new();
+        let mut model = VecDeque::new();
+        let mut state = 0x2545_f491u32;
+        for value in 0..300 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            match state % 3 {
+                0 => {
+                    list.push_front(value);
+                    model.push_front(value);
+                }
+                1 => {
+                    list.push_back(value);
+                    model.push_back(value);
+                }
+                _ => assert_eq!(list.pop_front(), model.pop_front()),
+            }
+            assert_matches(&list, &model);
+        }
+    }
+
+    #[test]
+    fn popping_the_last_node_resets_the_tail() {
+        let mut list = RawLinked::new();
+        list.push_back(1);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), None);
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_matches(&list, &VecDeque::from(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn iter_mut_and_the_mutable_peeks_edit_in_place() {
+        let mut list: RawLinked<u32> = (0..10).collect();
+        for data in list.iter_mut() {
+            *data *= 10;
+        }
+        *list.peek_mut().unwrap() += 1;
+        *list.back_mut().unwrap() += 2;
+        list.push_back(100);
+        assert!(list.iter().copied().eq([1, 10, 20, 30, 40, 50, 60, 70, 80, 92, 100].iter().copied()));
+        assert!(list.into_iter().eq([1, 10, 20, 30, 40, 50, 60, 70, 80, 92, 100].iter().copied()));
+    }
+
+    #[test]
+    fn dropping_a_non_empty_list_drops_every_element_once() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list = RawLinked::new();
+        for i in 0..20 {
+            if i % 2 == 0 {
+                list.push_back(Tracked(drops.clone()));
+            } else {
+                list.push_front(Tracked(drops.clone()));
+            }
+        }
+        drop(list.pop_front());
+        assert_eq!(drops.get(), 1);
+        drop(list);
+        assert_eq!(drops.get(), 20);
+    }
+
+    #[test]
+    fn a_partly_consumed_into_iter_drops_the_rest() {
+        let drops = Rc::new(Cell::new(0));
+        let list: RawLinked<Tracked> = (0..5).map(|_| Tracked(drops.clone())).collect();
+        let mut iter = list.into_iter();
+        drop(iter.next());
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+    }
+}
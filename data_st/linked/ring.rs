@@ -0,0 +1,219 @@
+//! Fixed capacity double ended queue over a circular buffer
+
+use std::fmt;
+
+/// What a push does when the buffer is full
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Overflow {
+    /// The push fails and hands the element back
+    Reject,
+
+    /// The element at the opposite end is evicted to make room
+    Overwrite,
+}
+
+
+/// Deque holding at most `capacity` elements, allocated once
+#[derive(Clone)]
+pub struct RingBuffer<T> {
+    slots: Vec<Option<T>>,
+
+    /// Slot of the front element
+    head: usize,
+
+    len: usize,
+    overflow: Overflow
+}
+
+/// Iterates from the front; `rev()` iterates from the back
+pub struct Iter<'a, T> {
+    buffer: &'a RingBuffer<T>,
+    front: usize,
+    back: usize
+}
+
+
+impl<T> RingBuffer<T> {
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        if capacity == 0 {
+            panic!("ring buffer capacity must be positive");
+        }
+        RingBuffer {
+            slots: (0..capacity).map(|_| None).collect(),
+            head: 0,
+            len: 0,
+            overflow
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.slots.len()
+    }
+
+    /// Adds `data` at the back. When full, a `Reject` buffer returns
+    /// `Err(data)`, an `Overwrite` one evicts the front element and
+    /// returns it as `Ok(Some(evicted))`.
+    pub fn push_back(&mut self, data: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Reject => return Err(data),
+                Overflow::Overwrite => self.pop_front(),
+            }
+        } else {
+            None
+        };
+
+        let slot = self.slot(self.len);
+        self.slots[slot] = Some(data);
+        self.len += 1;
+        Ok(evicted)
+    }
+
+    /// Adds `data` at the front. When full, a `Reject` buffer returns
+    /// `Err(data)`, an `Overwrite` one evicts the back element and
+    /// returns it as `Ok(Some(evicted))`.
+    pub fn push_front(&mut self, data: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Reject => return Err(data),
+                Overflow::Overwrite => self.pop_back(),
+            }
+        } else {
+            None
+        };
+
+        self.head = self.slot(self.slots.len() - 1);
+        self.slots[self.head] = Some(data);
+        self.len += 1;
+        Ok(evicted)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let data = self.slots[self.head].take();
+        self.head = self.slot(1);
+        self.len -= 1;
+        data
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        self.slots[slot].take()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Element `index` places from the front
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.slots[self.slot(index)].as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        self.slots[slot].as_mut()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+        self.head = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            buffer: self,
+            front: 0,
+            back: self.len
+        }
+    }
+
+    /// Slot of the element `offset` places after the front
+    fn slot(&self, offset: usize) -> usize {
+        (self.head + offset) % self.slots.len()
+    }
+}
+
+
+impl<T: fmt::Debug> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RingBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.buffer.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.buffer.get(self.back)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,82 @@
+//! Serde support for `Linked`, with the `serde` feature. A list is a
+//! sequence of its elements from the head, like a `Vec`.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::Linked;
+
+/// Builds a list from a sequence, pushing each element at the back
+struct LinkedVisitor<T>(PhantomData<T>);
+
+
+impl<T: Serialize> Serialize for Linked<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for data in self.iter() {
+            seq.serialize_element(data)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Linked<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(LinkedVisitor(PhantomData))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for LinkedVisitor<T> {
+    type Value = Linked<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Linked<T>, A::Error> {
+        let mut list = Linked::new();
+        while let Some(data) = seq.next_element()? {
+            list.push_back(data);
+        }
+        Ok(list)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_empty_list_round_trips() {
+        let list: Linked<u32> = Linked::new();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[]");
+        let back: Linked<u32> = serde_json::from_str(&json).unwrap();
+        assert!(back.is_empty());
+    }
+
+    #[test]
+    fn nested_lists_round_trip_in_order() {
+        let list: Linked<Linked<String>> = vec![
+            vec!["a".to_string(), "b".to_string()].into(),
+            Linked::new(),
+            vec!["c".to_string()].into(),
+        ].into();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, r#"[["a","b"],[],["c"]]"#);
+
+        let back: Linked<Linked<String>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, list);
+        assert_eq!(back.last().and_then(|inner| inner.last()).map(String::as_str), Some("c"));
+    }
+
+    #[test]
+    fn anything_but_a_sequence_is_rejected() {
+        assert!(serde_json::from_str::<Linked<u32>>("{}").is_err());
+        assert!(serde_json::from_str::<Linked<u32>>(r#"[1, "two"]"#).is_err());
+    }
+}
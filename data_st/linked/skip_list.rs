@@ -0,0 +1,452 @@
+//! Skip list ordered map
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+use crate::memory::MemoryFootprint;
+
+/// Levels a node can reach, plenty for any list that fits in memory
+const MAX_LEVEL: usize = 32;
+
+struct SkipNode<K, V> {
+    key: K,
+    value: V,
+
+    /// Next node on each level the node takes part in, as an index
+    /// into the list's nodes
+    next: Vec<Option<usize>>
+}
+
+
+/// Ordered map where each node is also linked on a random number of
+/// express levels, halving in population level by level, for O(log n)
+/// expected search, insert and remove. Nodes live in a vector and link
+/// by index, so the list needs no unsafe code.
+pub struct SkipList<K, V> {
+    nodes: Vec<Option<SkipNode<K, V>>>,
+
+    /// Slots of removed nodes, reused by the next inserts
+    free: Vec<usize>,
+
+    /// First node on each level
+    head: Vec<Option<usize>>,
+
+    len: usize,
+
+    /// xorshift state choosing node levels
+    rng: u64
+}
+
+/// Entries in key order, from `SkipList::iter` or `SkipList::range`
+pub struct Range<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    next: Option<usize>,
+
+    /// First node past the range, `None` to run to the end of the list
+    end: Option<usize>
+}
+
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// List with randomly seeded levels
+    pub fn new() -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        SkipList::with_seed(seed)
+    }
+
+    /// List whose levels are drawn from `seed`, so the same inserts
+    /// always build the same structure
+    pub fn with_seed(seed: u64) -> Self {
+        SkipList {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: Vec::new(),
+            len: 0,
+            rng: seed | 1
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Levels currently in use
+    pub fn height(&self) -> usize {
+        self.head.len()
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let update = self.predecessors(|k| k < &key);
+        if let Some(next) = self.successor(&update) {
+            let node = self.node_mut(next);
+            if node.key == key {
+                return Some(std::mem::replace(&mut node.value, value));
+            }
+        }
+
+        let level = self.random_level();
+        let mut next = Vec::with_capacity(level);
+        for l in 0..level {
+            next.push(match update.get(l) {
+                Some(&prev) => self.next_of(prev, l),
+                None => None,
+            });
+        }
+
+        let node = SkipNode {
+            key,
+            value,
+            next
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+
+        for l in 0..level {
+            match update.get(l) {
+                Some(&prev) => self.set_next(prev, l, Some(index)),
+                None => self.head.push(Some(index)),
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.lower_bound(|k| k < key)?;
+        let node = self.node(index);
+        if node.key == *key {
+            Some(&node.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.lower_bound(|k| k < key)?;
+        let node = self.node_mut(index);
+        if node.key == *key {
+            Some(&mut node.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let update = self.predecessors(|k| k < key);
+        let index = self.successor(&update)?;
+        if self.node(index).key != *key {
+            return None;
+        }
+
+        let node = self.nodes[index].take().unwrap();
+        for (l, next) in node.next.into_iter().enumerate() {
+            self.set_next(update[l], l, next);
+        }
+        while self.head.last() == Some(&None) {
+            self.head.pop();
+        }
+        self.free.push(index);
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.head.clear();
+        self.len = 0;
+    }
+
+    /// Entry with the smallest key
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let node = self.node((*self.head.first()?)?);
+        Some((&node.key, &node.value))
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range {
+            list: self,
+            next: self.head.first().copied().flatten(),
+            end: None
+        }
+    }
+
+    /// Entries whose keys fall in `range`. Both ends are found in
+    /// O(log n) expected; a range that starts after it ends is empty.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let mut next = match range.start_bound() {
+            Bound::Included(start) => self.lower_bound(|k| k < start),
+            Bound::Excluded(start) => self.lower_bound(|k| k <= start),
+            Bound::Unbounded => self.head.first().copied().flatten(),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.lower_bound(|k| k <= end),
+            Bound::Excluded(end) => self.lower_bound(|k| k < end),
+            Bound::Unbounded => None,
+        };
+        if let (Some(first), Some(stop)) = (next, end) {
+            if self.node(first).key > self.node(stop).key {
+                next = None;
+            }
+        }
+        Range {
+            list: self,
+            next,
+            end
+        }
+    }
+
+    /// Last node on every level that `before` holds for, `None` for the head
+    fn predecessors<F>(&self, before: F) -> Vec<Option<usize>>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut update = vec![None; self.head.len()];
+        let mut current = None;
+        for level in (0..self.head.len()).rev() {
+            while let Some(next) = self.next_of(current, level) {
+                if !before(&self.node(next).key) {
+                    break;
+                }
+                current = Some(next);
+            }
+            update[level] = current;
+        }
+        update
+    }
+
+    /// First node that `before` doesn't hold for
+    fn lower_bound<F>(&self, before: F) -> Option<usize>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let update = self.predecessors(before);
+        self.successor(&update)
+    }
+
+    /// Node right after the bottom level predecessor in `update`
+    fn successor(&self, update: &[Option<usize>]) -> Option<usize> {
+        self.next_of(*update.first()?, 0)
+    }
+
+    /// Level of a new node: one, plus one per coin flip coming up heads
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<K, V> SkipList<K, V> {
+    fn node(&self, index: usize) -> &SkipNode<K, V> {
+        self.nodes[index].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut SkipNode<K, V> {
+        self.nodes[index].as_mut().unwrap()
+    }
+
+    fn next_of(&self, prev: Option<usize>, level: usize) -> Option<usize> {
+        match prev {
+            Some(index) => self.node(index).next[level],
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, prev: Option<usize>, level: usize, next: Option<usize>) {
+        match prev {
+            Some(index) => self.node_mut(index).next[level] = next,
+            None => self.head[level] = next,
+        }
+    }
+}
+
+
+impl<K: MemoryFootprint, V: MemoryFootprint> MemoryFootprint for SkipNode<K, V> {
+    fn heap_size(&self) -> usize {
+        self.key.heap_size() + self.value.heap_size() + self.next.heap_size()
+    }
+}
+
+impl<K: MemoryFootprint, V: MemoryFootprint> MemoryFootprint for SkipList<K, V> {
+    fn heap_size(&self) -> usize {
+        self.nodes.heap_size() + self.free.heap_size() + self.head.heap_size()
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        SkipList::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let index = self.next?;
+        if Some(index) == self.end {
+            self.next = None;
+            return None;
+        }
+        let node = self.list.node(index);
+        self.next = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V>;
+
+    fn into_iter(self) -> Range<'a, K, V> {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::testing::{check_map, random_map_ops};
+
+    /// Levels of every node in key order
+    fn levels<K: Ord, V>(list: &SkipList<K, V>) -> Vec<usize> {
+        let mut levels = Vec::new();
+        let mut next = list.head.first().copied().flatten();
+        while let Some(index) = next {
+            levels.push(list.node(index).next.len());
+            next = list.node(index).next[0];
+        }
+        levels
+    }
+
+    fn bounds(key: i32) -> [Bound<i32>; 3] {
+        [Bound::Included(key), Bound::Excluded(key), Bound::Unbounded]
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn the_same_seed_builds_the_same_levels() {
+        let keys: Vec<u32> = (0..2_000).map(|i| i * 7919 % 2_003).collect();
+        let build = |seed| {
+            let mut list = SkipList::with_seed(seed);
+            keys.iter().for_each(|&key| { list.insert(key, ()); });
+            list
+        };
+
+        let (first, second) = (build(42), build(42));
+        assert_eq!(levels(&first), levels(&second));
+        assert_eq!(first.height(), second.height());
+        assert_ne!(levels(&first), levels(&build(1234)));
+
+        // about half the nodes reach each next level
+        let tall = levels(&first).iter().filter(|&&level| level > 1).count();
+        assert!(tall > 800 && tall < 1_200, "{} of 2000 nodes above level 1", tall);
+    }
+
+    #[test]
+    fn removed_slots_are_reused_with_the_same_structure() {
+        let mut list = SkipList::with_seed(7);
+        let mut again = SkipList::with_seed(7);
+        for key in 0..100 {
+            list.insert(key, key);
+            again.insert(key, key);
+        }
+        for key in (0..100).step_by(3) {
+            assert_eq!(list.remove(&key), again.remove(&key));
+        }
+        for key in 100..150 {
+            list.insert(key, key);
+            again.insert(key, key);
+        }
+        assert_eq!(levels(&list), levels(&again));
+        assert_eq!(list.nodes.len(), 116);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn ranges_match_a_btree_map_for_every_bound() {
+        let model: BTreeMap<i32, i32> = (0..40).step_by(2).map(|key| (key, -key)).collect();
+        let list: SkipList<i32, i32> = model.iter().map(|(&k, &v)| (k, v)).collect();
+
+        for start in -1..42 {
+            for end in start..42 {
+                for &low in bounds(start).iter() {
+                    for &high in bounds(end).iter() {
+                        // BTreeMap panics on an empty range with both ends excluded
+                        if start == end && low == Bound::Excluded(start) && high == Bound::Excluded(end) {
+                            continue;
+                        }
+                        let expected: Vec<_> = model.range((low, high)).collect();
+                        let actual: Vec<_> = list.range((low, high)).collect();
+                        assert_eq!(actual, expected, "{:?}..{:?}", low, high);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn an_inverted_range_is_empty() {
+        let list: SkipList<i32, ()> = (0..10).map(|key| (key, ())).collect();
+        assert_eq!(list.range((Bound::Included(6), Bound::Excluded(3))).count(), 0);
+        assert_eq!(list.range(5..5).count(), 0);
+        assert_eq!(list.range((Bound::Excluded(4), Bound::Excluded(5))).count(), 0);
+        assert_eq!(list.range(20..).count(), 0);
+        assert_eq!(list.range(..-1).count(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "safe code only, too slow under Miri")]
+    fn seeded_lists_match_a_btree_map() {
+        for seed in 1..=5 {
+            check_map(&mut SkipList::with_seed(seed), &random_map_ops(seed, 2_000, 64)).unwrap();
+        }
+    }
+}
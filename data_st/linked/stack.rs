@@ -0,0 +1,55 @@
+//! Last in, first out stack over `Linked`
+
+use crate::{Iter, Linked};
+
+/// Elements come out in the reverse order they were pushed
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Stack<T> {
+    list: Linked<T>
+}
+
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack {
+            list: Linked::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn push(&mut self, data: T) {
+        self.list.add(data);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+
+    /// Element the next `pop` returns
+    pub fn peek(&self) -> Option<&T> {
+        self.list.peek()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.list.peek_mut()
+    }
+
+    /// Iterates from the top of the stack down
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+}
+
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Stack::new()
+    }
+}
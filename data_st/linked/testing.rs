@@ -0,0 +1,668 @@
+//! Model based checks running the structures side by side with std
+//! collections, and proptest `Arbitrary` impls, for fuzzing code built
+//! on them
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::{ArenaBst, Bst, HashTable, Linked, SkipList, Unrolled};
+
+/// Operation on a list. Indexes are taken modulo the valid range when
+/// the operation runs, so any sequence of operations is valid.
+#[derive(Clone,Debug,PartialEq)]
+pub enum ListOp<T> {
+    PushFront(T),
+    PushBack(T),
+    PopFront,
+    PopBack,
+    Insert(usize, T),
+    RemoveAt(usize),
+    Get(usize),
+    Reverse,
+    Clear,
+}
+
+/// Operation on a map
+#[derive(Clone,Debug,PartialEq)]
+pub enum MapOp<K, V> {
+    Insert(K, V),
+    Remove(K),
+    Get(K),
+    Clear,
+}
+
+
+/// List that `check_list` can drive
+pub trait ListModel<T> {
+    fn push_front(&mut self, data: T);
+    fn push_back(&mut self, data: T);
+    fn pop_front(&mut self) -> Option<T>;
+    fn pop_back(&mut self) -> Option<T>;
+    fn insert(&mut self, index: usize, data: T);
+    fn remove_at(&mut self, index: usize) -> Option<T>;
+    fn get(&self, index: usize) -> Option<&T>;
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn to_vec(&self) -> Vec<&T>;
+
+    /// Reverses in place, or `false` if the list can't
+    fn reverse(&mut self) -> bool;
+}
+
+/// Map that `check_map` can drive
+pub trait MapModel<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn get(&self, key: &K) -> Option<&V>;
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries sorted by key
+    fn to_sorted_vec(&self) -> Vec<(&K, &V)>;
+}
+
+
+/// Runs `ops` on `list` and on a `VecDeque`, comparing every result and
+/// the contents after each step. The error names the first step that
+/// disagreed.
+pub fn check_list<T, L>(list: &mut L, ops: &[ListOp<T>]) -> Result<(), String>
+where
+    T: Clone + PartialEq + fmt::Debug,
+    L: ListModel<T>,
+{
+    let mut model = VecDeque::new();
+    for (step, op) in ops.iter().enumerate() {
+        let (got, expected) = match op.clone() {
+            ListOp::PushFront(data) => {
+                list.push_front(data.clone());
+                model.push_front(data);
+                (None, None)
+            }
+            ListOp::PushBack(data) => {
+                list.push_back(data.clone());
+                model.push_back(data);
+                (None, None)
+            }
+            ListOp::PopFront => (list.pop_front(), model.pop_front()),
+            ListOp::PopBack => (list.pop_back(), model.pop_back()),
+            ListOp::Insert(index, data) => {
+                let index = index % (model.len() + 1);
+                list.insert(index, data.clone());
+                model.insert(index, data);
+                (None, None)
+            }
+            ListOp::RemoveAt(index) => {
+                let index = index % (model.len() + 1);
+                (list.remove_at(index), model.remove(index))
+            }
+            ListOp::Get(index) => {
+                let index = index % (model.len() + 1);
+                (list.get(index).cloned(), model.get(index).cloned())
+            }
+            ListOp::Reverse => {
+                if list.reverse() {
+                    model.make_contiguous().reverse();
+                }
+                (None, None)
+            }
+            ListOp::Clear => {
+                list.clear();
+                model.clear();
+                (None, None)
+            }
+        };
+
+        if got != expected {
+            return Err(format!("step {} ({:?}) returned {:?}, expected {:?}", step, op, got, expected));
+        }
+        if list.len() != model.len() || !list.to_vec().into_iter().eq(model.iter()) {
+            return Err(format!("after step {} ({:?}) the list is {:?}, expected {:?}", step, op, list.to_vec(), model));
+        }
+    }
+    Ok(())
+}
+
+/// Runs `ops` on `map` and on a `BTreeMap`, comparing every result and
+/// the contents after each step. The error names the first step that
+/// disagreed.
+pub fn check_map<K, V, M>(map: &mut M, ops: &[MapOp<K, V>]) -> Result<(), String>
+where
+    K: Ord + Clone + fmt::Debug,
+    V: Clone + PartialEq + fmt::Debug,
+    M: MapModel<K, V>,
+{
+    let mut model = BTreeMap::new();
+    for (step, op) in ops.iter().enumerate() {
+        let (got, expected) = match op.clone() {
+            MapOp::Insert(key, value) => (map.insert(key.clone(), value.clone()), model.insert(key, value)),
+            MapOp::Remove(key) => (map.remove(&key), model.remove(&key)),
+            MapOp::Get(key) => (map.get(&key).cloned(), model.get(&key).cloned()),
+            MapOp::Clear => {
+                map.clear();
+                model.clear();
+                (None, None)
+            }
+        };
+
+        if got != expected {
+            return Err(format!("step {} ({:?}) returned {:?}, expected {:?}", step, op, got, expected));
+        }
+        if map.len() != model.len() || !map.to_sorted_vec().into_iter().eq(model.iter()) {
+            return Err(format!("after step {} ({:?}) the map is {:?}, expected {:?}", step, op, map.to_sorted_vec(), model));
+        }
+    }
+    Ok(())
+}
+
+/// `count` pseudo random list operations drawn from `seed`, pushing
+/// values below 1000
+pub fn random_list_ops(seed: u64, count: usize) -> Vec<ListOp<u32>> {
+    let mut rng = XorShift::new(seed);
+    (0..count)
+        .map(|_| {
+            let value = (rng.next() % 1000) as u32;
+            let index = rng.next() as usize;
+            match rng.next() % 20 {
+                0..=3 => ListOp::PushFront(value),
+                4..=7 => ListOp::PushBack(value),
+                8..=9 => ListOp::PopFront,
+                10..=11 => ListOp::PopBack,
+                12..=13 => ListOp::Insert(index, value),
+                14..=15 => ListOp::RemoveAt(index),
+                16..=17 => ListOp::Get(index),
+                18 => ListOp::Reverse,
+                _ => ListOp::Clear,
+            }
+        })
+        .collect()
+}
+
+/// `count` pseudo random map operations drawn from `seed`, on keys
+/// below `keys` so they collide
+pub fn random_map_ops(seed: u64, count: usize, keys: u32) -> Vec<MapOp<u32, u32>> {
+    let mut rng = XorShift::new(seed);
+    let keys = keys.max(1) as u64;
+    (0..count)
+        .map(|_| {
+            let key = (rng.next() % keys) as u32;
+            match rng.next() % 100 {
+                0..=44 => MapOp::Insert(key, rng.next() as u32),
+                45..=69 => MapOp::Remove(key),
+                70..=98 => MapOp::Get(key),
+                _ => MapOp::Clear,
+            }
+        })
+        .collect()
+}
+
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+
+impl<T> ListModel<T> for Linked<T> {
+    fn push_front(&mut self, data: T) {
+        self.add(data);
+    }
+
+    fn push_back(&mut self, data: T) {
+        Linked::push_back(self, data);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        Linked::pop_back(self)
+    }
+
+    fn insert(&mut self, index: usize, data: T) {
+        Linked::insert(self, index, data);
+    }
+
+    fn remove_at(&mut self, index: usize) -> Option<T> {
+        Linked::remove_at(self, index)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        Linked::get(self, index)
+    }
+
+    fn len(&self) -> usize {
+        Linked::len(self)
+    }
+
+    fn clear(&mut self) {
+        Linked::clear(self);
+    }
+
+    fn to_vec(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+
+    fn reverse(&mut self) -> bool {
+        Linked::reverse(self);
+        true
+    }
+}
+
+impl<T> ListModel<T> for Unrolled<T> {
+    fn push_front(&mut self, data: T) {
+        self.add(data);
+    }
+
+    fn push_back(&mut self, data: T) {
+        Unrolled::push_back(self, data);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        Unrolled::pop_back(self)
+    }
+
+    fn insert(&mut self, index: usize, data: T) {
+        Unrolled::insert(self, index, data);
+    }
+
+    fn remove_at(&mut self, index: usize) -> Option<T> {
+        Unrolled::remove_at(self, index)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        Unrolled::get(self, index)
+    }
+
+    fn len(&self) -> usize {
+        Unrolled::len(self)
+    }
+
+    fn clear(&mut self) {
+        Unrolled::clear(self);
+    }
+
+    fn to_vec(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+
+    fn reverse(&mut self) -> bool {
+        Unrolled::reverse(self);
+        true
+    }
+}
+
+impl<K: Ord, V> MapModel<K, V> for Bst<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        Bst::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        Bst::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        Bst::get(self, key)
+    }
+
+    fn len(&self) -> usize {
+        Bst::len(self)
+    }
+
+    fn clear(&mut self) {
+        Bst::clear(self);
+    }
+
+    fn to_sorted_vec(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Ord, V> MapModel<K, V> for ArenaBst<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        ArenaBst::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        ArenaBst::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        ArenaBst::get(self, key)
+    }
+
+    fn len(&self) -> usize {
+        ArenaBst::len(self)
+    }
+
+    fn clear(&mut self) {
+        ArenaBst::clear(self);
+    }
+
+    fn to_sorted_vec(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Ord, V> MapModel<K, V> for SkipList<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        SkipList::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        SkipList::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        SkipList::get(self, key)
+    }
+
+    fn len(&self) -> usize {
+        SkipList::len(self)
+    }
+
+    fn clear(&mut self) {
+        SkipList::clear(self);
+    }
+
+    fn to_sorted_vec(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Ord + Hash, V, S: BuildHasher> MapModel<K, V> for HashTable<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashTable::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashTable::remove(self, key)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        HashTable::get(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashTable::len(self)
+    }
+
+    fn clear(&mut self) {
+        HashTable::clear(self);
+    }
+
+    fn to_sorted_vec(&self) -> Vec<(&K, &V)> {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+
+/// Strategies building the structures from generated elements, and the
+/// operations from generated values and indexes
+#[cfg(feature = "testing")]
+mod arbitrary {
+    use std::hash::Hash;
+
+    use proptest::collection::SizeRange;
+    use proptest::prelude::*;
+
+    use super::{ListOp, MapOp};
+    use crate::{ArenaBst, Bst, DoublyLinked, HashTable, Linked, SkipList, Trie, Unrolled};
+
+    impl<T: Arbitrary + Clone + 'static> Arbitrary for ListOp<T> {
+        type Parameters = T::Parameters;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: T::Parameters) -> Self::Strategy {
+            let data = any_with::<T>(args).boxed();
+            prop_oneof![
+                data.clone().prop_map(ListOp::PushFront),
+                data.clone().prop_map(ListOp::PushBack),
+                Just(ListOp::PopFront),
+                Just(ListOp::PopBack),
+                (any::<usize>(), data).prop_map(|(index, data)| ListOp::Insert(index, data)),
+                any::<usize>().prop_map(ListOp::RemoveAt),
+                any::<usize>().prop_map(ListOp::Get),
+                Just(ListOp::Reverse),
+                Just(ListOp::Clear),
+            ]
+            .boxed()
+        }
+    }
+
+    impl<K, V> Arbitrary for MapOp<K, V>
+    where
+        K: Arbitrary + Clone + 'static,
+        V: Arbitrary + Clone + 'static,
+    {
+        type Parameters = (K::Parameters, V::Parameters);
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with((key_args, value_args): Self::Parameters) -> Self::Strategy {
+            let key = any_with::<K>(key_args).boxed();
+            prop_oneof![
+                (key.clone(), any_with::<V>(value_args)).prop_map(|(key, value)| MapOp::Insert(key, value)),
+                key.clone().prop_map(MapOp::Remove),
+                key.prop_map(MapOp::Get),
+                Just(MapOp::Clear),
+            ]
+            .boxed()
+        }
+    }
+
+    impl<T: Arbitrary + 'static> Arbitrary for Linked<T> {
+        type Parameters = (SizeRange, T::Parameters);
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<T>>(args).prop_map(Linked::from).boxed()
+        }
+    }
+
+    impl<T: Arbitrary + 'static> Arbitrary for Unrolled<T> {
+        type Parameters = (SizeRange, T::Parameters);
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<T>>(args).prop_map(|items| items.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<T: Arbitrary + 'static> Arbitrary for DoublyLinked<T> {
+        type Parameters = (SizeRange, T::Parameters);
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<T>>(args).prop_map(|items| items.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<K, V> Arbitrary for Bst<K, V>
+    where
+        K: Arbitrary + Ord + 'static,
+        V: Arbitrary + 'static,
+    {
+        type Parameters = (SizeRange, (K::Parameters, V::Parameters));
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<(K, V)>>(args).prop_map(|entries| entries.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<K, V> Arbitrary for ArenaBst<K, V>
+    where
+        K: Arbitrary + Ord + 'static,
+        V: Arbitrary + 'static,
+    {
+        type Parameters = (SizeRange, (K::Parameters, V::Parameters));
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<(K, V)>>(args).prop_map(|entries| entries.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<K, V> Arbitrary for SkipList<K, V>
+    where
+        K: Arbitrary + Ord + 'static,
+        V: Arbitrary + 'static,
+    {
+        type Parameters = (SizeRange, (K::Parameters, V::Parameters));
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<(K, V)>>(args).prop_map(|entries| entries.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<K, V> Arbitrary for HashTable<K, V>
+    where
+        K: Arbitrary + Hash + Eq + 'static,
+        V: Arbitrary + 'static,
+    {
+        type Parameters = (SizeRange, (K::Parameters, V::Parameters));
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+            any_with::<Vec<(K, V)>>(args).prop_map(|entries| entries.into_iter().collect()).boxed()
+        }
+    }
+
+    impl<V: Arbitrary + 'static> Arbitrary for Trie<V> {
+        type Parameters = (SizeRange, V::Parameters);
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with((size, value_args): Self::Parameters) -> Self::Strategy {
+            proptest::collection::vec(("[a-c]{0,6}", any_with::<V>(value_args)), size)
+                .prop_map(|entries: Vec<(String, V)>| {
+                    let mut trie = Trie::new();
+                    for (key, value) in entries {
+                        trie.insert(&key, value);
+                    }
+                    trie
+                })
+                .boxed()
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [u64; 5] = [1, 7, 42, 0x9e37_79b9, u64::MAX];
+
+    /// Operations per seed, fewer under Miri, which runs far slower
+    const OPS: usize = if cfg!(miri) { 200 } else { 2000 };
+
+    #[test]
+    fn linked_matches_a_vec_deque() {
+        for &seed in SEEDS.iter() {
+            check_list(&mut Linked::new(), &random_list_ops(seed, OPS)).unwrap();
+        }
+    }
+
+    #[test]
+    fn unrolled_matches_a_vec_deque() {
+        for &seed in SEEDS.iter() {
+            check_list(&mut Unrolled::new(), &random_list_ops(seed, OPS)).unwrap();
+        }
+    }
+
+    #[test]
+    fn bst_matches_a_btree_map() {
+        for &seed in SEEDS.iter() {
+            check_map(&mut Bst::new(), &random_map_ops(seed, OPS, 64)).unwrap();
+        }
+    }
+
+    #[test]
+    fn arena_bst_matches_a_btree_map() {
+        for &seed in SEEDS.iter() {
+            check_map(&mut ArenaBst::new(), &random_map_ops(seed, OPS, 64)).unwrap();
+        }
+    }
+
+    #[test]
+    fn skip_list_matches_a_btree_map() {
+        for &seed in SEEDS.iter() {
+            check_map(&mut SkipList::with_seed(seed), &random_map_ops(seed, OPS, 64)).unwrap();
+        }
+    }
+
+    #[test]
+    fn hash_table_matches_a_btree_map() {
+        for &seed in SEEDS.iter() {
+            check_map(&mut HashTable::new(), &random_map_ops(seed, OPS, 64)).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_disagreement_names_the_step() {
+        let ops = [ListOp::PushBack(1), ListOp::PopFront];
+        let mut list: Unrolled<u32> = vec![0].into_iter().collect();
+        let error = check_list(&mut list, &ops).unwrap_err();
+        assert!(error.starts_with("after step 0"), "{}", error);
+    }
+
+    #[cfg(feature = "testing")]
+    mod arbitrary {
+        use proptest::prelude::*;
+
+        use super::*;
+        use crate::Trie;
+
+        proptest! {
+            #[test]
+            fn generated_list_ops_match_the_model(ops in proptest::collection::vec(any::<ListOp<u8>>(), 0..200)) {
+                prop_assert_eq!(check_list(&mut Linked::new(), &ops), Ok(()));
+            }
+
+            #[test]
+            fn generated_map_ops_match_the_model(ops in proptest::collection::vec(any::<MapOp<u8, u8>>(), 0..200)) {
+                prop_assert_eq!(check_map(&mut SkipList::new(), &ops), Ok(()));
+            }
+
+            #[test]
+            fn generated_lists_hold_their_elements(items in any::<Vec<u8>>()) {
+                let list = Linked::from(items.clone());
+                prop_assert_eq!(Vec::from(list), items);
+            }
+
+            #[test]
+            fn generated_structures_are_consistent(list in any::<Linked<u8>>(), tree in any::<Bst<u8, u8>>(), trie in any::<Trie<u8>>()) {
+                prop_assert_eq!(list.iter().count(), list.len());
+                prop_assert_eq!(tree.iter().count(), tree.len());
+                prop_assert_eq!(trie.iter().count(), trie.len());
+            }
+        }
+    }
+}
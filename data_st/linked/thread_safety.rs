@@ -0,0 +1,207 @@
+//! Which types can be sent to or shared with other threads.
+//!
+//! A container owns its elements, so it is `Send` when they are `Send`
+//! and `Sync` when they are `Sync`, whether the compiler derives that or
+//! an `unsafe impl` states it over raw pointers. There are four exceptions:
+//!
+//! - `PersistentList` shares its nodes through `Arc`, so it needs elements
+//!   that are both `Send` and `Sync` to be either
+//! - `ConcurrentStack` is `Send` and `Sync` for any `Send` elements, since
+//!   they are moved in and out but never shared
+//! - `BloomFilter` only stores bits, so it is always both
+//! - `LruCache` is `Sync` even though its eviction callback is only `Send`
+//!
+//! Iterators, cursors and chunks borrow a container, so they follow the
+//! references they hand out. Shared ones are `Send` and `Sync` when the
+//! elements are `Sync`. Mutable ones (`IterMut`, `CursorMut`, `Drain`) are
+//! `Send` when the elements are `Send`, and `Sync` when they are `Sync`.
+//!
+//! The functions below are never called. They spell these rules out for
+//! every type, so the build fails when a change breaks one. A container of
+//! elements that aren't thread safe isn't thread safe either:
+//!
+//! ```compile_fail
+//! use linked::Linked;
+//! use std::rc::Rc;
+//!
+//! fn send<T: Send>(_: T) {}
+//! send(Linked::<Rc<i32>>::new());
+//! ```
+//!
+//! ```compile_fail
+//! use linked::DoublyLinked;
+//! use std::cell::Cell;
+//!
+//! fn send<T: Send>(_: T) {}
+//! let list: DoublyLinked<Cell<i32>> = DoublyLinked::new();
+//! send(list.iter());
+//! ```
+//!
+//! The exceptions hold their elements to the same rules. A
+//! `PersistentList` of `Send` elements that aren't `Sync` is not `Send`:
+//!
+//! ```compile_fail
+//! use linked::PersistentList;
+//! use std::cell::Cell;
+//!
+//! fn send<T: Send>(_: T) {}
+//! send(PersistentList::<Cell<i32>>::new());
+//! ```
+//!
+//! and an `LruCache` whose values aren't `Sync` is not `Sync`:
+//!
+//! ```compile_fail
+//! use linked::LruCache;
+//! use std::cell::Cell;
+//!
+//! fn sync<T: Sync>(_: &T) {}
+//! let cache: LruCache<u32, Cell<i32>> = LruCache::new(4);
+//! sync(&cache);
+//! ```
+
+#![allow(dead_code)]
+
+use crate::merkle::{MerkleProof, StdHasher};
+use crate::*;
+
+fn send<T: Send>() {}
+
+fn sync<T: Sync>() {}
+
+
+fn sendable_elements<T: Send, K: Send, V: Send>() {
+    send::<Linked<T>>();
+    send::<IntoIter<T>>();
+    send::<IterMut<'_, T>>();
+    send::<CursorMut<'_, T>>();
+    send::<Drain<'_, T>>();
+    send::<Unrolled<T>>();
+    send::<unrolled::IntoIter<T>>();
+    send::<unrolled::IterMut<'_, T>>();
+    send::<unrolled::CursorMut<'_, T>>();
+    send::<unrolled::Drain<'_, T>>();
+    send::<DoublyLinked<T>>();
+    send::<doubly::NodeHandle<T>>();
+    send::<doubly::IntoIter<T>>();
+    send::<doubly::IterMut<'_, T>>();
+    send::<doubly::CursorMut<'_, T>>();
+    send::<Circular<T>>();
+    send::<circular::CursorMut<'_, T>>();
+    #[cfg(feature = "raw")]
+    send::<RawLinked<T>>();
+    #[cfg(feature = "raw")]
+    send::<raw::IntoIter<T>>();
+    #[cfg(feature = "raw")]
+    send::<raw::IterMut<'_, T>>();
+    send::<Arena<T>>();
+    send::<arena::IterMut<'_, T>>();
+    send::<ArenaList<T>>();
+    send::<Queue<T>>();
+    send::<Stack<T>>();
+    send::<RingBuffer<T>>();
+    send::<FenwickTree<T>>();
+    send::<MerkleProof<T>>();
+    send::<graph::ShortestPaths<T>>();
+    send::<Bst<K, V>>();
+    send::<ArenaBst<K, V>>();
+    send::<SkipList<K, V>>();
+    send::<HashTable<K, V>>();
+    send::<LruCache<K, V>>();
+    send::<Graph<K, V>>();
+    send::<Trie<V>>();
+
+    send::<ConcurrentStack<T>>();
+    sync::<ConcurrentStack<T>>();
+}
+
+fn shareable_elements<T: Sync, K: Sync, V: Sync>() {
+    sync::<Linked<T>>();
+    sync::<IntoIter<T>>();
+    send::<Iter<'_, T>>();
+    sync::<Iter<'_, T>>();
+    sync::<IterMut<'_, T>>();
+    sync::<CursorMut<'_, T>>();
+    sync::<Drain<'_, T>>();
+    send::<Chunks<'_, T>>();
+    sync::<Chunks<'_, T>>();
+    send::<Chunk<'_, T>>();
+    sync::<Chunk<'_, T>>();
+    sync::<Unrolled<T>>();
+    send::<unrolled::Iter<'_, T>>();
+    sync::<unrolled::Iter<'_, T>>();
+    sync::<unrolled::IterMut<'_, T>>();
+    sync::<unrolled::CursorMut<'_, T>>();
+    sync::<unrolled::Drain<'_, T>>();
+    send::<unrolled::Chunks<'_, T>>();
+    sync::<unrolled::Chunks<'_, T>>();
+    send::<unrolled::Chunk<'_, T>>();
+    sync::<unrolled::Chunk<'_, T>>();
+    sync::<DoublyLinked<T>>();
+    sync::<doubly::NodeHandle<T>>();
+    send::<doubly::Iter<'_, T>>();
+    sync::<doubly::Iter<'_, T>>();
+    sync::<doubly::IterMut<'_, T>>();
+    sync::<doubly::CursorMut<'_, T>>();
+    sync::<Circular<T>>();
+    send::<circular::Iter<'_, T>>();
+    sync::<circular::Iter<'_, T>>();
+    sync::<circular::CursorMut<'_, T>>();
+    #[cfg(feature = "raw")]
+    sync::<RawLinked<T>>();
+    #[cfg(feature = "raw")]
+    send::<raw::Iter<'_, T>>();
+    #[cfg(feature = "raw")]
+    sync::<raw::Iter<'_, T>>();
+    #[cfg(feature = "raw")]
+    sync::<raw::IterMut<'_, T>>();
+    sync::<Arena<T>>();
+    send::<arena::Iter<'_, T>>();
+    sync::<arena::Iter<'_, T>>();
+    sync::<ArenaList<T>>();
+    send::<arena_list::Iter<'_, T>>();
+    sync::<arena_list::Iter<'_, T>>();
+    sync::<Queue<T>>();
+    sync::<Stack<T>>();
+    sync::<RingBuffer<T>>();
+    send::<ring::Iter<'_, T>>();
+    sync::<FenwickTree<T>>();
+    sync::<MerkleProof<T>>();
+    sync::<graph::ShortestPaths<T>>();
+    sync::<Bst<K, V>>();
+    send::<bst::Iter<'_, K, V>>();
+    sync::<ArenaBst<K, V>>();
+    send::<arena_bst::Iter<'_, K, V>>();
+    sync::<SkipList<K, V>>();
+    send::<skip_list::Range<'_, K, V>>();
+    sync::<HashTable<K, V>>();
+    send::<hash_table::Iter<'_, K, V>>();
+    sync::<LruCache<K, V>>();
+    sync::<Graph<K, V>>();
+    send::<graph::Bfs<'_, K, V>>();
+    send::<graph::Dfs<'_, K, V>>();
+    sync::<Trie<V>>();
+    send::<trie::Iter<'_, V>>();
+}
+
+fn sendable_and_shareable_elements<T: Send + Sync>() {
+    send::<PersistentList<T>>();
+    sync::<PersistentList<T>>();
+    send::<persistent::Iter<'_, T>>();
+}
+
+fn any_elements<T: ?Sized>() {
+    send::<BloomFilter<T>>();
+    sync::<BloomFilter<T>>();
+}
+
+fn without_elements() {
+    send::<arena::Index>();
+    sync::<arena::Index>();
+    send::<MerkleTree<StdHasher>>();
+    sync::<MerkleTree<StdHasher>>();
+}
+
+fn segment_tree<T: Send + Sync, F: Send + Sync>() {
+    send::<SegmentTree<T, F>>();
+    sync::<SegmentTree<T, F>>();
+}
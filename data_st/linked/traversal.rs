@@ -0,0 +1,244 @@
+//! Visiting the elements of any structure, and algorithms written once
+//! on top of that
+
+use std::ops::ControlFlow;
+
+use crate::graph::NodeId;
+use crate::{ArenaBst, Bst, DoublyLinked, Graph, Linked, SkipList};
+
+/// Structure whose elements can be visited one by one, stopping early
+/// when the visitor breaks. `'a` is the borrow of the structure, so
+/// items may be references into it.
+pub trait Traversable<'a> {
+    type Item;
+
+    /// Calls `visit` on every element in the structure's own order until
+    /// it returns `Break`, and returns that break
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut(Self::Item) -> ControlFlow<B>;
+
+    /// Combines every element into `init`, in traversal order
+    fn fold<A, F>(&'a self, init: A, mut f: F) -> A
+    where
+        F: FnMut(A, Self::Item) -> A,
+    {
+        let mut acc = Some(init);
+        let _ = self.traverse(|item| {
+            acc = acc.take().map(|acc| f(acc, item));
+            ControlFlow::<()>::Continue(())
+        });
+        acc.unwrap()
+    }
+}
+
+
+/// First element `pred` holds for
+pub fn find<'a, S, P>(structure: &'a S, mut pred: P) -> Option<S::Item>
+where
+    S: Traversable<'a> + ?Sized,
+    P: FnMut(&S::Item) -> bool,
+{
+    match structure.traverse(|item| if pred(&item) { ControlFlow::Break(item) } else { ControlFlow::Continue(()) }) {
+        ControlFlow::Break(item) => Some(item),
+        ControlFlow::Continue(()) => None,
+    }
+}
+
+/// Whether `pred` holds for some element, stopping at the first one
+pub fn any<'a, S, P>(structure: &'a S, mut pred: P) -> bool
+where
+    S: Traversable<'a> + ?Sized,
+    P: FnMut(&S::Item) -> bool,
+{
+    find(structure, |item| pred(item)).is_some()
+}
+
+pub fn count<'a, S>(structure: &'a S) -> usize
+where
+    S: Traversable<'a> + ?Sized,
+{
+    structure.fold(0, |count, _| count + 1)
+}
+
+/// Largest element, the last of several equal ones
+pub fn max<'a, S>(structure: &'a S) -> Option<S::Item>
+where
+    S: Traversable<'a> + ?Sized,
+    S::Item: Ord,
+{
+    structure.fold(None, |best: Option<S::Item>, item| match best {
+        Some(best) if best > item => Some(best),
+        _ => Some(item),
+    })
+}
+
+/// Element with the largest key, the last of several equal ones
+pub fn max_by_key<'a, S, K, F>(structure: &'a S, mut key: F) -> Option<S::Item>
+where
+    S: Traversable<'a> + ?Sized,
+    K: Ord,
+    F: FnMut(&S::Item) -> K,
+{
+    structure.fold(None, |best: Option<(K, S::Item)>, item| {
+        let item_key = key(&item);
+        match best {
+            Some((best_key, best)) if best_key > item_key => Some((best_key, best)),
+            _ => Some((item_key, item)),
+        }
+    })
+    .map(|(_, item)| item)
+}
+
+
+impl<'a, T: 'a> Traversable<'a> for Linked<T> {
+    type Item = &'a T;
+
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut(&'a T) -> ControlFlow<B>,
+    {
+        self.iter().try_for_each(visit)
+    }
+}
+
+impl<'a, T: 'a> Traversable<'a> for DoublyLinked<T> {
+    type Item = &'a T;
+
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut(&'a T) -> ControlFlow<B>,
+    {
+        self.iter().try_for_each(visit)
+    }
+}
+
+/// In key order
+impl<'a, K: Ord + 'a, V: 'a> Traversable<'a> for Bst<K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut((&'a K, &'a V)) -> ControlFlow<B>,
+    {
+        self.iter().try_for_each(visit)
+    }
+}
+
+/// In key order
+impl<'a, K: Ord + 'a, V: 'a> Traversable<'a> for ArenaBst<K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut((&'a K, &'a V)) -> ControlFlow<B>,
+    {
+        self.iter().try_for_each(visit)
+    }
+}
+
+/// In key order
+impl<'a, K: Ord + 'a, V: 'a> Traversable<'a> for SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn traverse<B, F>(&'a self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut((&'a K, &'a V)) -> ControlFlow<B>,
+    {
+        self.iter().try_for_each(visit)
+    }
+}
+
+/// Every node with its weight, in the order nodes were added
+impl<'a, N: 'a, E: 'a> Traversable<'a> for Graph<N, E> {
+    type Item = (NodeId, &'a N);
+
+    fn traverse<B, F>(&'a self, mut visit: F) -> ControlFlow<B>
+    where
+        F: FnMut((NodeId, &'a N)) -> ControlFlow<B>,
+    {
+        for id in 0..self.node_count() {
+            visit((id, self.node(id).unwrap()))?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Traverses `structure`, breaking at the element `stop` holds for,
+    /// and returns how many elements were visited
+    fn visits_until<'a, S, P>(structure: &'a S, mut stop: P) -> usize
+    where
+        S: Traversable<'a> + ?Sized,
+        P: FnMut(&S::Item) -> bool,
+    {
+        let mut visited = 0;
+        let _ = structure.traverse(|item| {
+            visited += 1;
+            if stop(&item) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        visited
+    }
+
+    #[test]
+    fn traverse_stops_at_the_first_break() {
+        let list: Linked<u32> = (0..100).collect();
+        assert_eq!(visits_until(&list, |&&data| data == 4), 5);
+        assert_eq!(visits_until(&list, |_| false), 100);
+        assert_eq!(list.traverse(|&data| if data >= 10 { ControlFlow::Break(data) } else { ControlFlow::Continue(()) }), ControlFlow::Break(10));
+
+        let doubly: DoublyLinked<u32> = (0..100).collect();
+        assert_eq!(visits_until(&doubly, |&&data| data == 0), 1);
+
+        let tree: Bst<u32, ()> = [50, 20, 80, 10, 30].iter().map(|&key| (key, ())).collect();
+        assert_eq!(visits_until(&tree, |&(&key, _)| key == 30), 3);
+
+        let arena: ArenaBst<u32, ()> = [50, 20, 80, 10, 30].iter().map(|&key| (key, ())).collect();
+        assert_eq!(visits_until(&arena, |&(&key, _)| key == 30), 3);
+
+        let skip: SkipList<u32, ()> = (0..100).rev().map(|key| (key, ())).collect();
+        assert_eq!(visits_until(&skip, |&(&key, _)| key == 7), 8);
+
+        let mut graph: Graph<char, ()> = Graph::new_directed();
+        for weight in "abcde".chars() {
+            graph.add_node(weight);
+        }
+        assert_eq!(visits_until(&graph, |&(_, &weight)| weight == 'b'), 2);
+    }
+
+    #[test]
+    fn find_and_any_stop_at_the_first_match() {
+        let list: Linked<u32> = (0..1000).collect();
+        let mut calls = 0;
+        assert_eq!(find(&list, |&&data| { calls += 1; data % 7 == 6 }), Some(&6));
+        assert_eq!(calls, 7);
+
+        calls = 0;
+        assert!(any(&list, |&&data| { calls += 1; data == 2 }));
+        assert_eq!(calls, 3);
+
+        assert_eq!(find(&list, |&&data| data > 1000), None);
+        assert!(!any(&Linked::<u32>::new(), |_| true));
+    }
+
+    #[test]
+    fn fold_based_algorithms_visit_everything() {
+        let doubly: DoublyLinked<i32> = [3, -1, 7, 7, 2].iter().copied().collect();
+        assert_eq!(count(&doubly), 5);
+        assert_eq!(max(&doubly), Some(&7));
+        assert_eq!(max_by_key(&doubly, |&&data| -data), Some(&-1));
+        assert_eq!(doubly.fold(0, |sum, &data| sum + data), 18);
+
+        let tree: Bst<&str, u32> = vec![("b", 2), ("a", 9), ("c", 2)].into_iter().collect();
+        assert_eq!(max_by_key(&tree, |&(_, &value)| value), Some((&"a", &9)));
+        assert_eq!(max_by_key(&tree, |&(_, &value)| value == 2), Some((&"c", &2)));
+
+        let empty: Graph<u32, ()> = Graph::new_undirected();
+        assert_eq!(count(&empty), 0);
+        assert_eq!(max(&empty), None);
+    }
+}
@@ -0,0 +1,254 @@
+//! Prefix tree mapping string keys to values
+
+use std::collections::btree_map;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::memory::MemoryFootprint;
+
+struct TrieNode<V> {
+    value: Option<V>,
+
+    /// Ordered so walking the children visits keys lexicographically
+    children: BTreeMap<char, TrieNode<V>>
+}
+
+
+/// Map from strings to `V`, sharing the storage of common prefixes
+pub struct Trie<V> {
+    root: TrieNode<V>,
+    len: usize
+}
+
+/// Entries in lexicographic order of their keys
+pub struct Iter<'a, V> {
+    /// Key spelled by the path to `children`'s parent, and the children
+    /// still to visit under it
+    stack: Vec<(String, btree_map::Iter<'a, char, TrieNode<V>>)>,
+
+    /// Entry of the node the walk starts from, handed out first
+    first: Option<(String, &'a V)>,
+
+    /// Entries left, known only when iterating the whole trie
+    remaining: Option<usize>
+}
+
+
+impl<V> TrieNode<V> {
+    fn new() -> Self {
+        TrieNode {
+            value: None,
+            children: BTreeMap::new()
+        }
+    }
+
+    /// Removes `key` below this node, pruning nodes left without a value
+    /// or children. Recurses once per character of the key.
+    fn remove(&mut self, mut key: std::str::Chars) -> Option<V> {
+        let c = match key.next() {
+            Some(c) => c,
+            None => return self.value.take(),
+        };
+        let child = self.children.get_mut(&c)?;
+        let value = child.remove(key);
+        if child.value.is_none() && child.children.is_empty() {
+            self.children.remove(&c);
+        }
+        value
+    }
+}
+
+
+impl<V> Trie<V> {
+    pub fn new() -> Self {
+        Trie {
+            root: TrieNode::new(),
+            len: 0
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for c in key.chars() {
+            node = node.children.entry(c).or_insert_with(TrieNode::new);
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.node(key)?.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for c in key.chars() {
+            node = node.children.get_mut(&c)?;
+        }
+        node.value.as_mut()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes `key` and returns its value, dropping the branch that
+    /// only it used
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let value = self.root.remove(key.chars());
+        if value.is_some() {
+            self.len -= 1;
+        }
+        value
+    }
+
+    /// Whether some key starts with `prefix`
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        self.node(prefix).is_some_and(|node| node.value.is_some() || !node.children.is_empty())
+    }
+
+    /// Keys starting with `prefix`, in lexicographic order
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.iter_prefix(prefix).map(|(key, _)| key).collect()
+    }
+
+    /// Entries whose keys start with `prefix`, in lexicographic order
+    pub fn iter_prefix(&self, prefix: &str) -> Iter<'_, V> {
+        match self.node(prefix) {
+            Some(node) => Iter::new(prefix.to_string(), node, None),
+            None => Iter {
+                stack: Vec::new(),
+                first: None,
+                remaining: Some(0)
+            },
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = String> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter::new(String::new(), &self.root, Some(self.len))
+    }
+
+    fn node(&self, key: &str) -> Option<&TrieNode<V>> {
+        let mut node = &self.root;
+        for c in key.chars() {
+            node = node.children.get(&c)?;
+        }
+        Some(node)
+    }
+}
+
+
+impl<'a, V> Iter<'a, V> {
+    fn new(key: String, node: &'a TrieNode<V>, remaining: Option<usize>) -> Self {
+        Iter {
+            first: node.value.as_ref().map(|value| (key.clone(), value)),
+            stack: vec![(key, node.children.iter())],
+            remaining
+        }
+    }
+
+    fn count_one(&mut self) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+    }
+}
+
+
+impl<V: MemoryFootprint> MemoryFootprint for TrieNode<V> {
+    fn heap_size(&self) -> usize {
+        self.value.heap_size() + self.children.heap_size()
+    }
+}
+
+impl<V: MemoryFootprint> MemoryFootprint for Trie<V> {
+    fn heap_size(&self) -> usize {
+        self.root.heap_size()
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Trie::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Trie<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'k, V> FromIterator<(&'k str, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (&'k str, V)>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        for (key, value) in iter {
+            trie.insert(key, value);
+        }
+        trie
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<(String, &'a V)> {
+        if let Some(entry) = self.first.take() {
+            self.count_one();
+            return Some(entry);
+        }
+
+        while let Some((prefix, children)) = self.stack.last_mut() {
+            let (c, child) = match children.next() {
+                Some(next) => next,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let mut key = prefix.clone();
+            key.push(*c);
+            let entry = child.value.as_ref().map(|value| (key.clone(), value));
+            self.stack.push((key, child.children.iter()));
+            if let Some(entry) = entry {
+                self.count_one();
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (0, None),
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Trie<V> {
+    type Item = (String, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Iter<'a, V> {
+        self.iter()
+    }
+}
@@ -0,0 +1,1374 @@
+//! Unrolled linked list, storing a small block of elements in every node
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
+use std::ptr::NonNull;
+use std::slice;
+
+use crate::memory::MemoryFootprint;
+
+/// Elements held by a node before it is split
+pub const NODE_CAPACITY: usize = 16;
+
+type Link<T> = Option<NonNull<UnrolledNode<T>>>;
+
+struct UnrolledNode<T> {
+    /// Never empty and never longer than `NODE_CAPACITY`
+    items: Vec<T>,
+    prev: Link<T>,
+    next: Link<T>
+}
+
+
+/// List with the API of `Linked` whose nodes hold up to `NODE_CAPACITY`
+/// elements each, so walking it touches far fewer allocations. The nodes
+/// link both ways, so either end is reached in O(1).
+pub struct Unrolled<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<Box<UnrolledNode<T>>>
+}
+
+/// Owning iterator, from the head to the last element
+pub struct IntoIter<T>(Unrolled<T>);
+
+pub struct Iter<'a, T> {
+    items: slice::Iter<'a, T>,
+    next: Option<&'a UnrolledNode<T>>
+}
+
+pub struct IterMut<'a, T> {
+    items: slice::IterMut<'a, T>,
+    next: Option<&'a mut UnrolledNode<T>>
+}
+
+/// Consecutive runs of elements, from `Unrolled::chunks`
+pub struct Chunks<'a, T> {
+    iter: Iter<'a, T>,
+    remaining: usize,
+    size: usize
+}
+
+/// View of up to `size` consecutive elements of a list
+pub struct Chunk<'a, T> {
+    iter: Iter<'a, T>,
+    len: usize
+}
+
+/// Removes a range of elements as it is iterated. Elements of the range
+/// that were not iterated are removed when it is dropped.
+pub struct Drain<'a, T> {
+    cursor: CursorMut<'a, T>,
+    remaining: usize
+}
+
+/// Edits a list in place while walking it. Past the last element the
+/// cursor sits on a "ghost" position, from which it wraps to the head.
+pub struct CursorMut<'a, T> {
+    list: &'a mut Unrolled<T>,
+
+    /// Node holding the current element, `None` on the ghost position
+    node: Link<T>,
+
+    /// Offset of the current element in `node`
+    offset: usize,
+
+    /// Index of the current element, the length on the ghost position
+    index: usize
+}
+
+
+impl<T> UnrolledNode<T> {
+    fn is_full(&self) -> bool {
+        self.items.len() == NODE_CAPACITY
+    }
+}
+
+
+impl<T> Unrolled<T> {
+    pub fn new() -> Self {
+        Unrolled {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pushes `data` at the head, shifting the head node's elements
+    pub fn add(&mut self, data: T) {
+        match self.head {
+            // the head is a node of this list, which we borrow mutably
+            Some(head) if unsafe { !(*head.as_ptr()).is_full() } => unsafe { (*head.as_ptr()).items.insert(0, data) },
+            _ => {
+                let node = new_node(data, None, self.head);
+                match self.head {
+                    Some(head) => unsafe { (*head.as_ptr()).prev = Some(node) },
+                    None => self.tail = Some(node),
+                }
+                self.head = Some(node);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Pushes `data` after the last element in O(1)
+    pub fn push_back(&mut self, data: T) {
+        match self.tail {
+            Some(tail) if unsafe { !(*tail.as_ptr()).is_full() } => unsafe { (*tail.as_ptr()).items.push(data) },
+            _ => {
+                let node = new_node(data, self.tail, None);
+                match self.tail {
+                    Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+                    None => self.head = Some(node),
+                }
+                self.tail = Some(node);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Drops the head element
+    pub fn remove(&mut self) {
+        self.pop();
+    }
+
+    /// Removes the head element and returns it
+    pub fn pop(&mut self) -> Option<T> {
+        let head = self.head?;
+        Some(unsafe { self.remove_in(head, 0) }.0)
+    }
+
+    /// Removes the last element and returns it in O(1)
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        let offset = unsafe { (*tail.as_ptr()).items.len() } - 1;
+        Some(unsafe { self.remove_in(tail, offset) }.0)
+    }
+
+    /// Moves every element of `other` to the end of this list in O(1),
+    /// leaving `other` empty. The nodes are linked as they are, without
+    /// merging the two that meet.
+    pub fn append(&mut self, other: &mut Unrolled<T>) {
+        let head = match other.head.take() {
+            Some(head) => head,
+            None => return,
+        };
+        match self.tail {
+            Some(tail) => unsafe {
+                (*tail.as_ptr()).next = Some(head);
+                (*head.as_ptr()).prev = Some(tail);
+            },
+            None => self.head = Some(head),
+        }
+        self.tail = other.tail.take();
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &node.as_ref().items[0] })
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|mut node| unsafe { &mut node.as_mut().items[0] })
+    }
+
+    /// First element, the same as `peek`
+    pub fn first(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    pub fn first_mut(&mut self) -> Option<&mut T> {
+        self.peek_mut()
+    }
+
+    /// Last element, in O(1) through the tail node
+    pub fn last(&self) -> Option<&T> {
+        self.tail.and_then(|node| unsafe { (*node.as_ptr()).items.last() })
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        self.tail.and_then(|node| unsafe { (*node.as_ptr()).items.last_mut() })
+    }
+
+    pub fn contains(&self, data: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|x| x == data)
+    }
+
+    /// Element at `index`, or `None` if it is out of bounds. Walks the
+    /// nodes from whichever end is closer.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.locate(index).map(|(node, offset)| unsafe { &node.as_ref().items[offset] })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.locate(index).map(|(mut node, offset)| unsafe { &mut node.as_mut().items[offset] })
+    }
+
+    /// Element `n` places after the head
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.get(n)
+    }
+
+    /// First element from the head that `pred` holds for
+    pub fn find<P>(&self, mut pred: P) -> Option<&T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().find(|data| pred(data))
+    }
+
+    /// Inserts `data` so that it ends up at `index`, splitting the node
+    /// it lands in if that node is full.
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, data: T) {
+        if index > self.len {
+            panic!("insert index {} is out of bounds for a list of length {}", index, self.len);
+        }
+        if index == self.len {
+            return self.push_back(data);
+        }
+
+        let (node, offset) = self.locate(index).unwrap();
+        unsafe {
+            let (node, offset) = self.make_room(node, offset);
+            (*node.as_ptr()).items.insert(offset, data);
+        }
+        self.len += 1;
+    }
+
+    /// Removes the element at `index` and returns it, or `None` if
+    /// `index` is out of bounds. A node that becomes small enough is
+    /// merged with the one after it.
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        let (node, offset) = self.locate(index)?;
+        Some(unsafe { self.remove_in(node, offset) }.0)
+    }
+
+    /// Unlinks the first element `pred` returns true for and returns it
+    pub fn remove_first_where<F>(&mut self, pred: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let index = self.iter().position(pred)?;
+        self.remove_at(index)
+    }
+
+    /// Keeps only the elements `keep` returns true for, in a single pass.
+    /// Nodes left empty are freed, the others keep what is left in them.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut next = self.head;
+        while let Some(node) = next {
+            unsafe {
+                next = (*node.as_ptr()).next;
+                let items = &mut (*node.as_ptr()).items;
+                let before = items.len();
+                items.retain(|data| keep(data));
+                self.len -= before - items.len();
+                if items.is_empty() {
+                    drop(self.unlink(node));
+                }
+            }
+        }
+    }
+
+    /// Drops every element, one node at a time
+    pub fn clear(&mut self) {
+        while let Some(head) = self.head {
+            drop(unsafe { self.unlink(head) });
+        }
+        self.len = 0;
+    }
+
+    /// Reverses the list in place, reversing each node and the links
+    /// between them
+    pub fn reverse(&mut self) {
+        let mut next = self.head;
+        while let Some(node) = next {
+            unsafe {
+                let node = &mut *node.as_ptr();
+                node.items.reverse();
+                mem::swap(&mut node.prev, &mut node.next);
+                next = node.prev;
+            }
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Splits the list in two at `index`. This list keeps the elements
+    /// before it and the rest are returned.
+    ///
+    /// Panics if `index` is greater than the length.
+    pub fn split_at(&mut self, index: usize) -> Unrolled<T> {
+        if index > self.len {
+            panic!("split index {} is out of bounds for a list of length {}", index, self.len);
+        }
+        if index == 0 {
+            return mem::take(self);
+        }
+        let (node, offset) = self.locate(index - 1).unwrap();
+        unsafe { self.split_after_offset(node, offset, index) }
+    }
+
+    /// Moves the first `n` elements to the back, keeping their order.
+    ///
+    /// Panics if `n` is greater than the length.
+    pub fn rotate_left(&mut self, n: usize) {
+        if n > self.len {
+            panic!("rotation {} is out of bounds for a list of length {}", n, self.len);
+        }
+        let mut rotated = self.split_at(n);
+        rotated.append(self);
+        *self = rotated;
+    }
+
+    /// Moves every element of `other` in after the element at `index`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn splice_after(&mut self, index: usize, mut other: Unrolled<T>) {
+        if index >= self.len {
+            panic!("splice index {} is out of bounds for a list of length {}", index, self.len);
+        }
+        let mut rest = self.split_at(index + 1);
+        self.append(&mut other);
+        self.append(&mut rest);
+    }
+
+    /// Removes consecutive repeated elements, keeping the first of each run
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes consecutive elements that map to the same key
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes each element `same` returns true for when passed it and
+    /// the element kept before it. The kept elements are packed into
+    /// full nodes again.
+    pub fn dedup_by<F>(&mut self, same: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut items: Vec<T> = mem::take(self).into_iter().collect();
+        items.dedup_by(same);
+        self.extend(items);
+    }
+
+    /// Splits the list into the elements `pred` returns true for and the
+    /// rest, keeping their order
+    pub fn partition<F>(self, mut pred: F) -> (Unrolled<T>, Unrolled<T>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut matching = Unrolled::new();
+        let mut rest = Unrolled::new();
+        for data in self {
+            if pred(&data) {
+                matching.push_back(data);
+            } else {
+                rest.push_back(data);
+            }
+        }
+        (matching, rest)
+    }
+
+    /// Sorts the list with a stable sort. The elements are moved into a
+    /// `Vec` to be sorted and packed into full nodes again.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    /// Stable sort, ordering the elements with `compare`
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut items: Vec<T> = mem::take(self).into_iter().collect();
+        items.sort_by(compare);
+        self.extend(items);
+    }
+
+    /// Whether every element is at most the next one
+    pub fn is_sorted(&self) -> bool
+    where
+        T: Ord,
+    {
+        let mut iter = self.iter();
+        let mut prev = match iter.next() {
+            Some(data) => data,
+            None => return true,
+        };
+        for data in iter {
+            if prev > data {
+                return false;
+            }
+            prev = data;
+        }
+        true
+    }
+
+    /// Inserts `data` into a sorted list, after any equal elements, so the
+    /// list stays sorted. O(1) when `data` goes last, otherwise a walk.
+    pub fn insert_sorted(&mut self, data: T)
+    where
+        T: Ord,
+    {
+        if self.last().is_none_or(|last| *last <= data) {
+            self.push_back(data);
+            return;
+        }
+        let index = self.iter().take_while(|existing| **existing <= data).count();
+        self.insert(index, data);
+    }
+
+    /// Searches a sorted list like `slice::binary_search`: `Ok` with the
+    /// index of the first element equal to `data`, or `Err` with the index
+    /// where it would be inserted. This walks from the head and stops at
+    /// the first element not less than `data`.
+    pub fn position_sorted(&self, data: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        for (index, existing) in self.iter().enumerate() {
+            match existing.cmp(data) {
+                Ordering::Less => {}
+                Ordering::Equal => return Ok(index),
+                Ordering::Greater => return Err(index),
+            }
+        }
+        Err(self.len)
+    }
+
+    /// Merges two sorted lists into one sorted list. Of equal elements,
+    /// the ones from `self` come first.
+    pub fn merge(self, other: Unrolled<T>) -> Unrolled<T>
+    where
+        T: Ord,
+    {
+        let mut merged = Unrolled::new();
+        let mut left = self.into_iter().peekable();
+        let mut right = other.into_iter().peekable();
+        loop {
+            let take_right = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => r < l,
+                (None, Some(_)) => true,
+                (_, None) => false,
+            };
+            match if take_right { right.next() } else { left.next() } {
+                Some(data) => merged.push_back(data),
+                None => return merged,
+            }
+        }
+    }
+
+    /// Alternates the elements of `self` and `other`, from the head of
+    /// `self`. Whatever is left of the longer list follows.
+    pub fn interleave(&mut self, other: Unrolled<T>) {
+        let mut mine = mem::take(self).into_iter();
+        let mut theirs = other.into_iter();
+        loop {
+            match (mine.next(), theirs.next()) {
+                (None, None) => return,
+                (a, b) => self.extend(a.into_iter().chain(b)),
+            }
+        }
+    }
+
+    /// Pairs of elements from both lists, stopping at the end of the
+    /// shorter one
+    pub fn zip<'a, U>(&'a self, other: &'a Unrolled<U>) -> std::iter::Zip<Iter<'a, T>, Iter<'a, U>> {
+        self.iter().zip(other.iter())
+    }
+
+    /// Views of `size` consecutive elements from the head, the last one
+    /// shorter if the length isn't a multiple of `size`.
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> Chunks<'_, T> {
+        if size == 0 {
+            panic!("chunk size must be positive");
+        }
+        Chunks {
+            iter: self.iter(),
+            remaining: self.len,
+            size
+        }
+    }
+
+    /// Removes the elements in `range`, handing them out as it goes.
+    ///
+    /// Panics if the range is decreasing or ends past the length.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        if start > end || end > self.len {
+            panic!("drain range {}..{} is out of bounds for a list of length {}", start, end, self.len);
+        }
+
+        let (node, offset) = match self.locate(start) {
+            Some((node, offset)) => (Some(node), offset),
+            None => (None, 0),
+        };
+        Drain {
+            cursor: CursorMut {
+                list: self,
+                node,
+                offset,
+                index: start
+            },
+            remaining: end - start
+        }
+    }
+
+    /// Cursor starting on the head, or on the ghost position if empty
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            node: self.head,
+            list: self,
+            offset: 0,
+            index: 0
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            items: [].iter(),
+            next: unsafe { borrow_link(self.head) }
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            items: [].iter_mut(),
+            next: unsafe { borrow_link_mut(self.head) }
+        }
+    }
+
+    /// Node holding `index` and the offset of the element within it,
+    /// walking from the closer end
+    fn locate(&self, index: usize) -> Option<(NonNull<UnrolledNode<T>>, usize)> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe {
+            if index < self.len / 2 {
+                let mut index = index;
+                let mut node = self.head?;
+                while index >= (*node.as_ptr()).items.len() {
+                    index -= (*node.as_ptr()).items.len();
+                    node = (*node.as_ptr()).next?;
+                }
+                Some((node, index))
+            } else {
+                let mut after = self.len - index;
+                let mut node = self.tail?;
+                while after > (*node.as_ptr()).items.len() {
+                    after -= (*node.as_ptr()).items.len();
+                    node = (*node.as_ptr()).prev?;
+                }
+                Some((node, (*node.as_ptr()).items.len() - after))
+            }
+        }
+    }
+
+    /// Splits `node` if it is full, returning where `offset` ended up
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list and `offset` at most its length.
+    unsafe fn make_room(&mut self, node: NonNull<UnrolledNode<T>>, offset: usize) -> (NonNull<UnrolledNode<T>>, usize) {
+        if !(*node.as_ptr()).is_full() {
+            return (node, offset);
+        }
+        let upper = self.split_node(node, NODE_CAPACITY / 2);
+        if offset > NODE_CAPACITY / 2 {
+            (upper, offset - NODE_CAPACITY / 2)
+        } else {
+            (node, offset)
+        }
+    }
+
+    /// Moves the items of `node` from `at` on into a new node linked
+    /// after it, and returns the new node
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list, with more than `at` items.
+    unsafe fn split_node(&mut self, node: NonNull<UnrolledNode<T>>, at: usize) -> NonNull<UnrolledNode<T>> {
+        let mut items = Vec::with_capacity(NODE_CAPACITY);
+        items.extend((*node.as_ptr()).items.drain(at..));
+        let next = (*node.as_ptr()).next;
+        let upper = NonNull::from(Box::leak(Box::new(UnrolledNode {
+            items,
+            prev: Some(node),
+            next
+        })));
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(upper),
+            None => self.tail = Some(upper),
+        }
+        (*node.as_ptr()).next = Some(upper);
+        upper
+    }
+
+    /// Cuts the list after `offset` in `node`, which is `index - 1`, and
+    /// returns the rest
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list holding element `index - 1` at `offset`.
+    unsafe fn split_after_offset(&mut self, node: NonNull<UnrolledNode<T>>, offset: usize, index: usize) -> Unrolled<T> {
+        if offset + 1 < (*node.as_ptr()).items.len() {
+            self.split_node(node, offset + 1);
+        }
+        let mut rest = Unrolled::new();
+        if let Some(next) = (*node.as_ptr()).next.take() {
+            (*next.as_ptr()).prev = None;
+            rest.head = Some(next);
+            rest.tail = self.tail;
+            rest.len = self.len - index;
+            self.tail = Some(node);
+            self.len = index;
+        }
+        rest
+    }
+
+    /// Removes the element at `offset` in `node`, freeing the node if it
+    /// empties or merging the one after it in if both fit in one. Returns
+    /// the element and where the element after it is now.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list, with more than `offset` items.
+    unsafe fn remove_in(&mut self, node: NonNull<UnrolledNode<T>>, offset: usize) -> (T, Link<T>, usize) {
+        let data = (*node.as_ptr()).items.remove(offset);
+        self.len -= 1;
+        if (*node.as_ptr()).items.is_empty() {
+            let next = (*node.as_ptr()).next;
+            drop(self.unlink(node));
+            return (data, next, 0);
+        }
+
+        if let Some(next) = (*node.as_ptr()).next {
+            if (*node.as_ptr()).items.len() + (*next.as_ptr()).items.len() <= NODE_CAPACITY {
+                let mut next = self.unlink(next);
+                (*node.as_ptr()).items.append(&mut next.items);
+            }
+        }
+        if offset < (*node.as_ptr()).items.len() {
+            (data, Some(node), offset)
+        } else {
+            (data, (*node.as_ptr()).next, 0)
+        }
+    }
+
+    /// Unlinks `node` from its neighbours and hands back ownership of it
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list. Its items are not counted out
+    /// of the length.
+    unsafe fn unlink(&mut self, node: NonNull<UnrolledNode<T>>) -> Box<UnrolledNode<T>> {
+        let node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        node
+    }
+}
+
+
+fn new_node<T>(data: T, prev: Link<T>, next: Link<T>) -> NonNull<UnrolledNode<T>> {
+    let mut items = Vec::with_capacity(NODE_CAPACITY);
+    items.push(data);
+    NonNull::from(Box::leak(Box::new(UnrolledNode {
+        items,
+        prev,
+        next
+    })))
+}
+
+/// Node `link` points to, borrowed for `'a`.
+///
+/// # Safety
+///
+/// The node must stay alive for `'a`, without being borrowed mutably.
+unsafe fn borrow_link<'a, T>(link: Link<T>) -> Option<&'a UnrolledNode<T>> {
+    link.map(|node| &*node.as_ptr())
+}
+
+/// Node `link` points to, borrowed mutably for `'a`.
+///
+/// # Safety
+///
+/// The node must stay alive for `'a`, without being borrowed otherwise.
+unsafe fn borrow_link_mut<'a, T>(link: Link<T>) -> Option<&'a mut UnrolledNode<T>> {
+    link.map(|node| &mut *node.as_ptr())
+}
+
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Index of the current element, `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.node.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        let offset = self.offset;
+        self.node.map(|mut node| unsafe { &mut node.as_mut().items[offset] })
+    }
+
+    /// Moves to the next element, from the last one to the ghost
+    /// position, and from the ghost position to the head
+    pub fn move_next(&mut self) {
+        match self.node {
+            Some(node) => {
+                self.index += 1;
+                self.offset += 1;
+                if self.offset == unsafe { (*node.as_ptr()).items.len() } {
+                    self.node = unsafe { (*node.as_ptr()).next };
+                    self.offset = 0;
+                }
+            }
+            None => {
+                self.node = self.list.head;
+                self.offset = 0;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Inserts `data` before the current element and stays on the
+    /// current one. On the ghost position this pushes at the back.
+    pub fn insert_before(&mut self, data: T) {
+        let node = match self.node {
+            Some(node) => node,
+            None => {
+                self.list.push_back(data);
+                self.index = self.list.len;
+                return;
+            }
+        };
+        unsafe {
+            let (node, offset) = self.list.make_room(node, self.offset);
+            (*node.as_ptr()).items.insert(offset, data);
+            self.node = Some(node);
+            self.offset = offset + 1;
+        }
+        self.index += 1;
+        self.list.len += 1;
+    }
+
+    /// Inserts `data` after the current element and stays on the
+    /// current one. On the ghost position this pushes at the head.
+    pub fn insert_after(&mut self, data: T) {
+        let node = match self.node {
+            Some(node) => node,
+            None => {
+                self.list.add(data);
+                self.index = self.list.len;
+                return;
+            }
+        };
+        unsafe {
+            let (node, offset) = self.list.make_room(node, self.offset);
+            (*node.as_ptr()).items.insert(offset + 1, data);
+            self.node = Some(node);
+            self.offset = offset;
+        }
+        self.list.len += 1;
+    }
+
+    /// Unlinks the current element and returns it, moving on to the
+    /// element after it
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.node?;
+        let (data, next, offset) = unsafe { self.list.remove_in(node, self.offset) };
+        self.node = next;
+        self.offset = offset;
+        Some(data)
+    }
+
+    /// Moves every element after the current one into a new list. On the
+    /// ghost position the whole list is moved.
+    pub fn split_after(&mut self) -> Unrolled<T> {
+        match self.node {
+            Some(node) => unsafe { self.list.split_after_offset(node, self.offset, self.index + 1) },
+            None => {
+                self.index = 0;
+                mem::take(self.list)
+            }
+        }
+    }
+}
+
+
+impl<'a, T> Chunk<'a, T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false, `chunks` never yields an empty view
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> std::iter::Take<Iter<'a, T>> {
+        self.iter.clone().take(self.len)
+    }
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            items: self.items.clone(),
+            next: self.next
+        }
+    }
+}
+
+impl<'a, T> Clone for Chunk<'a, T> {
+    fn clone(&self) -> Self {
+        Chunk {
+            iter: self.iter.clone(),
+            len: self.len
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Chunk<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+
+// The list owns its nodes, so it is as thread safe as its elements. The
+// iterators and chunks borrow nodes, and follow from the node impls.
+unsafe impl<T: Send> Send for Unrolled<T> {}
+
+unsafe impl<T: Sync> Sync for Unrolled<T> {}
+
+unsafe impl<T: Send> Send for UnrolledNode<T> {}
+
+unsafe impl<T: Sync> Sync for UnrolledNode<T> {}
+
+// The cursor only borrows the list, so it is as thread safe as
+// `&mut Unrolled<T>`
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+impl<T> Drop for Unrolled<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Unrolled<T> {
+    fn heap_size(&self) -> usize {
+        let mut size = 0;
+        let mut next = unsafe { borrow_link(self.head) };
+        while let Some(node) = next {
+            size += mem::size_of::<UnrolledNode<T>>() + node.items.heap_size();
+            next = unsafe { borrow_link(node.next) };
+        }
+        size
+    }
+}
+
+impl<T> Default for Unrolled<T> {
+    fn default() -> Self {
+        Unrolled::new()
+    }
+}
+
+impl<T: Clone> Clone for Unrolled<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Unrolled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Unrolled<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Unrolled<T> {}
+
+/// Lists compare element by element, like slices
+impl<T: PartialOrd> PartialOrd for Unrolled<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for Unrolled<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for Unrolled<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for data in self.iter() {
+            data.hash(state);
+        }
+    }
+}
+
+/// Formats the list as `[1 -> 2 -> 3]`, like `Linked`
+impl<T: fmt::Display> fmt::Display for Unrolled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, data) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{}", data)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<T> FromIterator<T> for Unrolled<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Unrolled::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for Unrolled<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Unrolled<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<Unrolled<T>> for Vec<T> {
+    fn from(list: Unrolled<T>) -> Self {
+        let mut vec = Vec::with_capacity(list.len());
+        vec.extend(list);
+        vec
+    }
+}
+
+/// Walks the nodes from the closer end, in O(n / `NODE_CAPACITY`).
+///
+/// Panics if `index` is out of bounds, see `get` for a checked version.
+impl<T> Index<usize> for Unrolled<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(data) => data,
+            None => panic!("index {} is out of bounds for a list of length {}", index, self.len),
+        }
+    }
+}
+
+/// Walks the nodes from the closer end, in O(n / `NODE_CAPACITY`).
+///
+/// Panics if `index` is out of bounds, see `get_mut` for a checked version.
+impl<T> IndexMut<usize> for Unrolled<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(data) => data,
+            None => panic!("index {} is out of bounds for a list of length {}", index, len),
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(data) = self.items.next() {
+                return Some(data);
+            }
+            let node = self.next.take()?;
+            self.items = node.items.iter();
+            // the iterator borrows the list, which keeps every node alive
+            self.next = unsafe { borrow_link(node.next) };
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        loop {
+            if let Some(data) = self.items.next() {
+                return Some(data);
+            }
+            let node = self.next.take()?;
+            // each node is handed out once, while the list is borrowed mutably
+            self.next = unsafe { borrow_link_mut(node.next) };
+            self.items = node.items.iter_mut();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = Chunk<'a, T>;
+
+    fn next(&mut self) -> Option<Chunk<'a, T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let len = self.size.min(self.remaining);
+        let chunk = Chunk {
+            iter: self.iter.clone(),
+            len
+        };
+        self.iter.nth(len - 1);
+        self.remaining -= len;
+        Some(chunk)
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.cursor.remove_current()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<T> IntoIterator for Unrolled<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Unrolled<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Unrolled<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> IntoIterator for Chunk<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::Take<Iter<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Walks the nodes both ways, checking the links, the tail, the length
+    /// and that no node is empty or over capacity
+    fn check<T>(list: &Unrolled<T>) {
+        let mut len = 0;
+        let mut prev = None;
+        let mut next = list.head;
+        while let Some(node) = next {
+            let node_ref = unsafe { node.as_ref() };
+            assert!(!node_ref.items.is_empty() && node_ref.items.len() <= NODE_CAPACITY);
+            assert_eq!(node_ref.prev, prev);
+            len += node_ref.items.len();
+            prev = Some(node);
+            next = node_ref.next;
+        }
+        assert_eq!(list.tail, prev);
+        assert_eq!(list.len, len);
+    }
+
+    fn to_vec<T: Clone>(list: &Unrolled<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn inserts_and_removes_anywhere_like_a_vec() {
+        let mut list = Unrolled::new();
+        let mut vec = Vec::new();
+        let mut state = 0x2545_f491_u64;
+        for step in 0..600 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let index = state as usize % (vec.len() + 1);
+            match state % 4 {
+                0 if !vec.is_empty() => {
+                    let index = index.min(vec.len() - 1);
+                    assert_eq!(list.remove_at(index), Some(vec.remove(index)));
+                }
+                1 => assert_eq!(list.pop_back(), vec.pop()),
+                _ => {
+                    list.insert(index, step);
+                    vec.insert(index, step);
+                }
+            }
+            check(&list);
+            assert_eq!(list.last(), vec.last());
+        }
+        assert_eq!(to_vec(&list), vec);
+        for (index, data) in vec.iter().enumerate() {
+            assert_eq!(list[index], *data);
+        }
+    }
+
+    #[test]
+    fn pops_from_both_ends() {
+        let mut list: Unrolled<u32> = (0..40).collect();
+        list.add(100);
+        assert_eq!(list.pop(), Some(100));
+        for expected in (20..40).rev() {
+            assert_eq!(list.pop_back(), Some(expected));
+            check(&list);
+        }
+        for expected in 0..20 {
+            assert_eq!(list.pop(), Some(expected));
+            check(&list);
+        }
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.first(), None);
+    }
+
+    #[test]
+    fn appends_splits_and_rotates() {
+        let mut list: Unrolled<u32> = (0..20).collect();
+        let mut other: Unrolled<u32> = (20..45).collect();
+        list.append(&mut other);
+        check(&list);
+        check(&other);
+        assert!(other.is_empty());
+        assert_eq!(to_vec(&list), (0..45).collect::<Vec<_>>());
+
+        let rest = list.split_at(7);
+        check(&list);
+        check(&rest);
+        assert_eq!(to_vec(&list), (0..7).collect::<Vec<_>>());
+        assert_eq!(to_vec(&rest), (7..45).collect::<Vec<_>>());
+
+        let mut list = rest;
+        list.rotate_left(10);
+        check(&list);
+        assert_eq!(to_vec(&list), (17..45).chain(7..17).collect::<Vec<_>>());
+
+        list.splice_after(0, vec![1, 2].into());
+        check(&list);
+        assert_eq!(to_vec(&list)[..4].to_vec(), vec![17, 1, 2, 18]);
+        list.push_back(99);
+        assert_eq!(list.last(), Some(&99));
+    }
+
+    #[test]
+    fn reverses_retains_and_dedups() {
+        let mut list: Unrolled<u32> = (0..50).collect();
+        list.reverse();
+        check(&list);
+        assert_eq!(to_vec(&list), (0..50).rev().collect::<Vec<_>>());
+
+        list.retain(|x| x % 16 != 3 && *x >= 8);
+        check(&list);
+        let expected: Vec<u32> = (8..50).rev().filter(|x| x % 16 != 3).collect();
+        assert_eq!(to_vec(&list), expected);
+
+        let mut list: Unrolled<u32> = vec![1, 1, 2, 3, 3, 3, 1].into();
+        list.dedup();
+        check(&list);
+        assert_eq!(to_vec(&list), vec![1, 2, 3, 1]);
+        list.dedup_by_key(|x| *x / 2);
+        assert_eq!(to_vec(&list), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn sorts_stably_and_merges() {
+        let mut list: Unrolled<(u8, usize)> = (0..70).map(|i| ((i * 7 % 5) as u8, i)).collect();
+        let mut vec = to_vec(&list);
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        vec.sort_by_key(|a| a.0);
+        check(&list);
+        assert_eq!(to_vec(&list), vec);
+
+        let evens: Unrolled<u32> = (0..30).map(|x| x * 2).collect();
+        let odds: Unrolled<u32> = (0..20).map(|x| x * 2 + 1).collect();
+        let mut merged = evens.merge(odds);
+        check(&merged);
+        assert!(merged.is_sorted());
+        merged.insert_sorted(7);
+        assert_eq!(merged.position_sorted(&7), Ok(7));
+        assert_eq!(merged.position_sorted(&100), Err(merged.len()));
+        assert!(merged.is_sorted());
+    }
+
+    #[test]
+    fn a_cursor_edits_in_place() {
+        let mut list: Unrolled<u32> = (0..20).collect();
+        let mut cursor = list.cursor_front_mut();
+        while let Some(data) = cursor.current() {
+            if *data % 3 == 0 {
+                cursor.remove_current();
+            } else {
+                let data = *data;
+                cursor.insert_before(data * 100);
+                cursor.move_next();
+            }
+        }
+        assert_eq!(cursor.index(), None);
+        cursor.insert_before(7);
+        cursor.insert_after(8);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 8));
+        check(&list);
+        let expected: Vec<u32> = Some(8).into_iter()
+            .chain((0..20).filter(|x| x % 3 != 0).flat_map(|x| vec![x * 100, x]))
+            .chain(Some(7))
+            .collect();
+        assert_eq!(to_vec(&list), expected);
+
+        let mut cursor = list.cursor_front_mut();
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+        let rest = cursor.split_after();
+        check(&list);
+        check(&rest);
+        assert_eq!(to_vec(&list), expected[..5].to_vec());
+        assert_eq!(to_vec(&rest), expected[5..].to_vec());
+    }
+
+    #[test]
+    fn drains_a_range_even_when_dropped_early() {
+        let mut list: Unrolled<u32> = (0..40).collect();
+        let drained: Vec<u32> = list.drain(10..30).collect();
+        check(&list);
+        assert_eq!(drained, (10..30).collect::<Vec<_>>());
+        assert_eq!(to_vec(&list), (0..10).chain(30..40).collect::<Vec<_>>());
+
+        let mut drain = list.drain(5..);
+        assert_eq!(drain.next(), Some(5));
+        drop(drain);
+        check(&list);
+        assert_eq!(to_vec(&list), (0..5).collect::<Vec<_>>());
+        assert_eq!(list.drain(5..).count(), 0);
+    }
+
+    #[test]
+    fn chunks_zips_and_interleaves() {
+        let list: Unrolled<u32> = (0..37).collect();
+        let chunks: Vec<Vec<u32>> = list.chunks(10).map(|chunk| chunk.iter().cloned().collect()).collect();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[3], (30..37).collect::<Vec<_>>());
+
+        let mut mine: Unrolled<u32> = vec![1, 3, 5].into();
+        mine.interleave(vec![2, 4, 6, 8, 10].into());
+        check(&mine);
+        assert_eq!(to_vec(&mine), vec![1, 2, 3, 4, 5, 6, 8, 10]);
+        assert_eq!(mine.zip(&list).map(|(a, b)| a + b).last(), Some(17));
+        assert_eq!(mine.to_string(), "[1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 8 -> 10]");
+        assert_eq!(Vec::from(mine.partition(|x| x % 4 == 0).0), vec![4, 8]);
+    }
+}
@@ -1,9 +1,7 @@
-use linked;
 use linked::Linked;
 
 fn main() {
     println!("Hello, world!");
-    linked::welcome();
     let mut eg = Linked::new();
     println!("{:?}",eg);
     eg.add(3);
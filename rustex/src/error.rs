@@ -0,0 +1,32 @@
+//! Error type covering the failures of both libraries
+
+use std::fmt;
+
+/// The blockchain reports errors as `String`s or `&'static str`s and the
+/// data structures as `&'static str`s; this keeps them apart behind one type.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum Error {
+    Blockchain(String),
+    DataStructure(&'static str),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Blockchain(message) => write!(f, "blockchain: {}", message),
+            Error::DataStructure(message) => write!(f, "data structure: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Errors of `Blockchain::append_block` and the other `String` results
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Blockchain(message)
+    }
+}
@@ -0,0 +1,51 @@
+//! Blake2b, the chain's hash, for the data structures' Merkle trees
+
+use blake2::{Blake2b, Digest};
+use blockchain::Block;
+use linked::merkle::{self, MerkleTree};
+
+use crate::{Error, Result};
+
+/// Prefixes keeping leaf and inner node digests apart
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+
+/// Merkle tree hasher over Blake2b, with digests as the chain stores them
+#[derive(Clone,Copy,Debug)]
+pub struct Blake2bHasher;
+
+
+impl merkle::Hasher for Blake2bHasher {
+    type Digest = Vec<u8>;
+
+    fn hash_leaf(data: &[u8]) -> Vec<u8> {
+        let mut hasher = Blake2b::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(data);
+        Vec::from(hasher.finalize().as_ref())
+    }
+
+    fn hash_nodes(left: &Vec<u8>, right: &Vec<u8>) -> Vec<u8> {
+        let mut hasher = Blake2b::new();
+        hasher.update([NODE_PREFIX]);
+        hasher.update(left);
+        hasher.update(right);
+        Vec::from(hasher.finalize().as_ref())
+    }
+}
+
+
+/// Blake2b digest of `data`, as used for block and transaction hashes
+pub fn blake2b(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Blake2b::new();
+    hasher.update(data);
+    Vec::from(hasher.finalize().as_ref())
+}
+
+/// Merkle tree whose leaves are the hashes of the block's transactions,
+/// in order. Fails for a block without transactions.
+pub fn transactions_tree(block: &Block) -> Result<MerkleTree<Blake2bHasher>> {
+    MerkleTree::from_leaves(block.transactions().iter().map(|transaction| transaction.calculate_hash()))
+        .map_err(Error::DataStructure)
+}
@@ -0,0 +1,12 @@
+//! One crate for both libraries of the repository: the blockchain and
+//! the data structures, plus what they share
+
+pub use ::blockchain;
+pub use linked as datastructures;
+
+pub mod error;
+pub mod hash;
+
+pub use error::{Error, Result};
+pub use hash::Blake2bHasher;
+pub use linked::MemoryFootprint;
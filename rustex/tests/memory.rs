@@ -0,0 +1,29 @@
+use rustex::blockchain::fixtures::{self, FixtureConfig};
+use rustex::datastructures::Linked;
+use rustex::MemoryFootprint;
+
+#[test]
+fn chain_state_grows_with_its_blocks() {
+    let small = fixtures::generate(&FixtureConfig {
+        blocks: 2,
+        ..FixtureConfig::default()
+    });
+    let large = fixtures::generate(&FixtureConfig {
+        blocks: 20,
+        ..FixtureConfig::default()
+    });
+
+    assert!(small.chain.heap_size() > 0);
+    assert!(large.chain.heap_size() > small.chain.heap_size());
+    assert!(large.chain.accounts().heap_size() > 0);
+}
+
+#[test]
+fn a_list_counts_its_nodes_and_what_they_own() {
+    let numbers: Linked<u64> = (0..10).collect();
+    let words: Linked<String> = (0..10).map(|_| String::from("word")).collect();
+
+    assert!(numbers.heap_size() >= 10 * std::mem::size_of::<u64>());
+    assert!(words.heap_size() >= numbers.heap_size() + 10 * "word".len());
+    assert_eq!(numbers.memory_footprint(), std::mem::size_of::<Linked<u64>>() + numbers.heap_size());
+}
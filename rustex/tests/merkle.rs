@@ -0,0 +1,42 @@
+use rustex::blockchain::fixtures::{self, FixtureConfig};
+use rustex::blockchain::Block;
+use rustex::datastructures::MerkleTree;
+use rustex::hash::transactions_tree;
+use rustex::{Blake2bHasher, Error};
+
+#[test]
+fn every_transaction_of_a_fixture_chain_has_a_valid_proof() {
+    let fixture = fixtures::generate(&FixtureConfig {
+        blocks: 5,
+        transactions_per_block: 7,
+        ..FixtureConfig::default()
+    });
+
+    for block in fixture.chain.blocks() {
+        let tree = transactions_tree(block).unwrap();
+        assert_eq!(tree.leaf_count(), block.get_transaction_count());
+
+        for (index, transaction) in block.transactions().iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            let hash = transaction.calculate_hash();
+            assert!(MerkleTree::<Blake2bHasher>::verify(tree.root(), &hash, &proof));
+        }
+    }
+}
+
+#[test]
+fn a_proof_does_not_verify_another_transaction() {
+    let fixture = fixtures::generate(&FixtureConfig::default());
+    let block = &fixture.chain.blocks()[1];
+    let tree = transactions_tree(block).unwrap();
+
+    let proof = tree.proof(0).unwrap();
+    let other = block.transactions()[1].calculate_hash();
+    assert!(!MerkleTree::<Blake2bHasher>::verify(tree.root(), &other, &proof));
+}
+
+#[test]
+fn an_empty_block_has_no_transactions_tree() {
+    let error = transactions_tree(&Block::new(None)).unwrap_err();
+    assert!(matches!(error, Error::DataStructure(_)));
+}